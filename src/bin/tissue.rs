@@ -29,20 +29,34 @@ fn main() {
 				exit(1);
 			});
 
-			match cli::run(command, &mut tissue_box) {
-				Ok(Some(out)) => print!("{out}"),
-				Ok(None) => {}
+			let code = match cli::run(command, &mut tissue_box) {
+				Ok(Some(out)) => {
+					print!("{out}");
+					0
+				}
+				Ok(None) => 0,
+				Err(cli::Error::Overdue(overdue)) => {
+					print!("{overdue}");
+					exit(1);
+				}
+				// Keep the links to the issues that were published, so they aren't published again,
+				// and forget the tissues whose attachments are already gone.
+				Err(msg @ (cli::Error::PartlyPublished(..) | cli::Error::PartlyPurged(..))) => {
+					error!("{msg}");
+					1
+				}
 				Err(msg) => {
 					error!("{msg}");
 					exit(1);
 				}
-			}
+			};
 			// cli::run can't manage saving because it needs to be run in unit tests,
-			// so just save after every run.
+			// so just save after every run, unless it failed and changed nothing.
 			if let Err(msg) = tissue_box.save(&cli.input) {
 				error!("failed to serialize tissue box: {msg}");
 				exit(1);
 			};
+			exit(code);
 		}
 		None => {
 			let original_hook = panic::take_hook();
@@ -62,6 +76,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use tissuebox::publish::{Issue, Plan, Publisher};
 
 	fn test_box() -> TissueBox {
 		let mut tissue_box = TissueBox::default();
@@ -79,14 +94,18 @@ mod tests {
 	#[test]
 	fn list_all() {
 		let mut tissue_box = test_box();
-		let command = cli::Command::List(cli::List { index: None, which: None });
+		let command = cli::Command::List(cli::List { index: None, which: None, ..Default::default() });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
 
 	#[test]
 	fn list_first() {
 		let mut tissue_box = test_box();
-		let command = cli::Command::List(cli::List { index: Some(0), which: None });
+		let command = cli::Command::List(cli::List {
+			index: Some(0),
+			which: None,
+			..Default::default()
+		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
 
@@ -96,6 +115,7 @@ mod tests {
 		let command = cli::Command::List(cli::List {
 			index: Some(0),
 			which: Some(cli::WhichList::Title),
+			..Default::default()
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
@@ -106,6 +126,7 @@ mod tests {
 		let command = cli::Command::List(cli::List {
 			index: Some(0),
 			which: Some(cli::WhichList::Description(cli::OptionIndex { index: None })),
+			..Default::default()
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
@@ -116,6 +137,7 @@ mod tests {
 		let command = cli::Command::List(cli::List {
 			index: Some(0),
 			which: Some(cli::WhichList::Description(cli::OptionIndex { index: Some(0) })),
+			..Default::default()
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
@@ -126,6 +148,7 @@ mod tests {
 		let command = cli::Command::List(cli::List {
 			index: Some(0),
 			which: Some(cli::WhichList::Tags),
+			..Default::default()
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 	}
@@ -133,7 +156,11 @@ mod tests {
 	#[test]
 	fn filtered_list_without_index() {
 		let mut tissue_box = test_box();
-		let command = cli::Command::List(cli::List { index: None, which: Some(cli::WhichList::Title) });
+		let command = cli::Command::List(cli::List {
+			index: None,
+			which: Some(cli::WhichList::Title),
+			..Default::default()
+		});
 		assert!(cli::run(command, &mut tissue_box).is_err());
 	}
 
@@ -152,7 +179,7 @@ mod tests {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Describe(cli::Describe { description: DESC.into(), index: Some(0) });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
-		assert_eq!(tissue_box.get(0).unwrap().description.get(1).map(|x| x.as_str()), Some(DESC));
+		assert_eq!(tissue_box.get(0).unwrap().description.get(1).map(|x| x.text.as_str()), Some(DESC));
 	}
 
 	#[test]
@@ -161,14 +188,14 @@ mod tests {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Describe(cli::Describe { description: DESC.into(), index: None });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
-		assert_eq!(tissue_box.get(1).unwrap().description.get(2).map(|x| x.as_str()), Some(DESC));
+		assert_eq!(tissue_box.get(1).unwrap().description.get(2).map(|x| x.text.as_str()), Some(DESC));
 	}
 
 	#[test]
 	fn tag() {
 		const TAG: &str = "good first issue";
 		let mut tissue_box = test_box();
-		let command = cli::Command::Tag(cli::Tag { tag: TAG.into(), index: Some(0) });
+		let command = cli::Command::Tag(cli::Tag { tag: TAG.into(), targets: targets("0") });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 		assert!(tissue_box.get(0).unwrap().tags.contains(TAG));
 	}
@@ -177,7 +204,7 @@ mod tests {
 	fn tag_last() {
 		const TAG: &str = "bug";
 		let mut tissue_box = test_box();
-		let command = cli::Command::Tag(cli::Tag { tag: TAG.into(), index: None });
+		let command = cli::Command::Tag(cli::Tag { tag: TAG.into(), targets: Default::default() });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 		assert!(tissue_box.get(1).unwrap().tags.contains(TAG));
 	}
@@ -185,15 +212,42 @@ mod tests {
 	#[test]
 	fn remove_tissue() {
 		let mut tissue_box = test_box();
-		let command = cli::Command::Remove(cli::Remove { index: 1, which: None });
+		let command = cli::Command::Remove(cli::Remove { targets: targets("1"), which: None });
 		assert!(cli::run(command, &mut tissue_box).is_ok());
 		assert!(tissue_box.get(1).is_none());
 	}
 
+	#[test]
+	fn remove_tissue_is_discarded() {
+		let mut tissue_box = test_box();
+		let command = cli::Command::Remove(cli::Remove { targets: targets("0"), which: None });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		assert_eq!(tissue_box.get_removed(0).unwrap().resolution, Some(Resolution::Discarded));
+		assert!(cli::run(cli::Command::Trash, &mut tissue_box).unwrap().unwrap().starts_with("0. [discarded] Foo"));
+		tissue_box.restore(0);
+		assert_eq!(tissue_box.get(1).unwrap().resolution, None);
+	}
+
+	#[test]
+	fn show_removed_tissue() {
+		let mut tissue_box = test_box();
+		let _ = tissue_box.close(
+			1,
+			Resolution::Published {
+				url: "https://github.com/evie-calico/tissuebox/issues/1".into(),
+				number: Some(1),
+			},
+		);
+		let command = cli::Command::Show(cli::Show { index: 0, trash: true });
+		assert!(cli::run(command, &mut tissue_box).unwrap().unwrap().contains("published to https://github.com/evie-calico/tissuebox/issues/1"));
+		let command = cli::Command::Show(cli::Show { index: 1, trash: true });
+		assert!(cli::run(command, &mut tissue_box).is_err());
+	}
+
 	#[test]
 	fn remove_missing_tissue() {
 		let mut tissue_box = test_box();
-		let command = cli::Command::Remove(cli::Remove { index: 2, which: None });
+		let command = cli::Command::Remove(cli::Remove { targets: targets("2"), which: None });
 		assert!(cli::run(command, &mut tissue_box).is_err());
 	}
 
@@ -201,7 +255,7 @@ mod tests {
 	fn remove_tissue_description() {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Remove(cli::Remove {
-			index: 1,
+			targets: targets("1"),
 			which: Some(cli::WhichRemove::Description(cli::Index { index: 1 })),
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
@@ -212,7 +266,7 @@ mod tests {
 	fn remove_missing_tissue_description() {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Remove(cli::Remove {
-			index: 1,
+			targets: targets("1"),
 			which: Some(cli::WhichRemove::Description(cli::Index { index: 2 })),
 		});
 		assert!(cli::run(command, &mut tissue_box).is_err());
@@ -222,7 +276,7 @@ mod tests {
 	fn remove_tissue_tag() {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Remove(cli::Remove {
-			index: 1,
+			targets: targets("1"),
 			which: Some(cli::WhichRemove::Tag(cli::TagName { tag: "good first issue".into() })),
 		});
 		assert!(cli::run(command, &mut tissue_box).is_ok());
@@ -232,9 +286,997 @@ mod tests {
 	fn remove_missing_tissue_tag() {
 		let mut tissue_box = test_box();
 		let command = cli::Command::Remove(cli::Remove {
-			index: 1,
+			targets: targets("1"),
 			which: Some(cli::WhichRemove::Tag(cli::TagName { tag: "null".into() })),
 		});
 		assert!(cli::run(command, &mut tissue_box).is_err());
 	}
+
+	#[test]
+	fn slug() {
+		let mut tissue_box = test_box();
+		tissue_box.create("Fix  the thing!".into());
+		tissue_box.create("???".into());
+		assert_eq!(tissue_box.get(2).unwrap().slug(), "fix-the-thing");
+		assert_eq!(tissue_box.get(3).unwrap().slug(), "tissue");
+	}
+
+	#[test]
+	fn finish_without_branch() {
+		let mut tissue_box = test_box();
+		let command = cli::Command::Finish(cli::Finish { index: 0, merge: false, force: false });
+		assert!(cli::run(command, &mut tissue_box).is_err());
+	}
+
+	/// Target the tissues at `indices`, such as "1-4,7".
+	fn targets(indices: &str) -> cli::Targets {
+		cli::Targets {
+			indices: Some(indices.parse().unwrap()),
+			queries: Vec::new(),
+		}
+	}
+
+	fn test_dir(name: &str) -> std::path::PathBuf {
+		let dir = env::temp_dir().join(format!("tissuebox-test-{}-{name}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	#[test]
+	fn hooks_chain_and_restore() {
+		const USER_HOOK: &str = "#!/bin/sh\necho user hook\n";
+		let dir = test_dir("hooks");
+		std::fs::write(dir.join("commit-msg"), USER_HOOK).unwrap();
+		tissuebox::hooks::install(&dir, "tissue".as_ref(), false).unwrap();
+		// Reinstalling must not chain our own hook.
+		tissuebox::hooks::install(&dir, "tissue".as_ref(), true).unwrap();
+		assert!(std::fs::read_to_string(dir.join("commit-msg")).unwrap().contains("--require-tissue"));
+		assert_eq!(std::fs::read_to_string(dir.join("commit-msg.tissuebox-chained")).unwrap(), USER_HOOK);
+		tissuebox::hooks::uninstall(&dir).unwrap();
+		assert_eq!(std::fs::read_to_string(dir.join("commit-msg")).unwrap(), USER_HOOK);
+		assert!(!dir.join("prepare-commit-msg").exists());
+		assert!(!dir.join("commit-msg.tissuebox-chained").exists());
+	}
+
+	#[test]
+	fn prepare_commit_msg() {
+		let mut tissue_box = test_box();
+		tissue_box.star(Some(1));
+		let file = test_dir("prepare-commit-msg").join("COMMIT_EDITMSG");
+		std::fs::write(&file, "# comment\n").unwrap();
+		let command = cli::Command::Hooks(cli::Hooks::PrepareCommitMsg(cli::PrepareCommitMsg { file: file.clone(), source: None, commit: None }));
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		assert_eq!(std::fs::read_to_string(&file).unwrap(), "Bar\n# comment\n");
+	}
+
+	#[test]
+	fn commit_msg_requires_tissue() {
+		let mut tissue_box = test_box();
+		let file = test_dir("commit-msg").join("COMMIT_EDITMSG");
+		std::fs::write(&file, "Foo\n\nbody\n").unwrap();
+		let command = cli::Command::Hooks(cli::Hooks::CommitMsg(cli::CommitMsg { file: file.clone(), require_tissue: true }));
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		std::fs::write(&file, "Baz\n").unwrap();
+		let command = cli::Command::Hooks(cli::Hooks::CommitMsg(cli::CommitMsg { file, require_tissue: true }));
+		assert!(cli::run(command, &mut tissue_box).is_err());
+	}
+
+	#[test]
+	fn scan_comments() {
+		let dir = test_dir("scan");
+		let file = dir.join("main.rs");
+		std::fs::write(&file, "// TODO: Parse arguments\nfn main() {} // FIXME(evie) handle errors\n/// Mentions TODO in prose\nlet s = \"#\"; /* HACK */\n").unwrap();
+		let markers = TissueBox::default().config().markers.clone();
+		let comments = tissuebox::scan::comments(std::slice::from_ref(&file), &markers).unwrap();
+		let found = comments.iter().map(|c| (c.marker.as_str(), c.title(), c.reference.line)).collect::<Vec<_>>();
+		assert_eq!(found, [("TODO", "Parse arguments".to_string(), 1), ("FIXME", "handle errors".to_string(), 2), ("HACK", format!("HACK in {}:4", file.display()), 4),]);
+
+		let mut tissue_box = test_box();
+		assert_eq!(tissuebox::scan::import(&mut tissue_box, &comments).len(), 3);
+		assert!(tissue_box.get(2).unwrap().tags.contains("todo"));
+		// Scanning again must not duplicate anything.
+		assert!(tissuebox::scan::import(&mut tissue_box, &comments).is_empty());
+
+		let check = tissuebox::scan::check(&tissue_box, std::slice::from_ref(&dir), &comments[1..], &markers);
+		assert!(check.untracked.is_empty());
+		assert_eq!(check.stale, [2]);
+
+		// Comments without text are still recognized once lines are added above them.
+		std::fs::write(&file, "use std::env;\n// TODO: Parse arguments\nfn main() {} // FIXME(evie) handle errors\n/// Mentions TODO in prose\nlet s = \"#\"; /* HACK */\n").unwrap();
+		let comments = tissuebox::scan::comments(std::slice::from_ref(&file), &markers).unwrap();
+		assert!(tissuebox::scan::import(&mut tissue_box, &comments).is_empty());
+		let check = tissuebox::scan::check(&tissue_box, std::slice::from_ref(&dir), &comments, &markers);
+		assert!(check.untracked.is_empty() && check.stale.is_empty());
+
+		// A comment left behind by a committed tissue kept for history is untracked again.
+		tissue_box.config_mut().workflow.keep_history = true;
+		assert!(tissue_box.close_committed(2, "abc1234".into()).is_some());
+		let check = tissuebox::scan::check(&tissue_box, std::slice::from_ref(&dir), &comments, &markers);
+		assert_eq!(check.untracked.iter().map(|comment| comment.title()).collect::<Vec<_>>(), ["Parse arguments"]);
+		assert!(check.stale.is_empty());
+		assert_eq!(tissuebox::scan::import(&mut tissue_box, &comments).len(), 1);
+
+		// Discarding a tissue doesn't bring its comment back on the next scan.
+		assert_eq!(tissue_box.get(3).unwrap().title, "handle errors");
+		assert!(tissue_box.remove(3).is_some());
+		assert!(tissuebox::scan::import(&mut tissue_box, &comments).is_empty());
+		assert!(tissuebox::scan::check(&tissue_box, std::slice::from_ref(&dir), &comments, &markers).untracked.is_empty());
+	}
+
+	#[test]
+	fn parse_reference() {
+		let reference: Reference = "src/lib.rs:10:5".parse().unwrap();
+		assert_eq!((reference.path.to_str(), reference.line, reference.column), (Some("src/lib.rs"), 10, Some(5)));
+		let reference: Reference = "src/lib.rs:10".parse().unwrap();
+		assert_eq!((reference.path.to_str(), reference.line, reference.column), (Some("src/lib.rs"), 10, None));
+		assert!("src/lib.rs".parse::<Reference>().is_err());
+		assert!("src/lib.rs:0".parse::<Reference>().is_err());
+	}
+
+	#[test]
+	fn reanchor_reference() {
+		let file = test_dir("reanchor").join("lib.rs");
+		std::fs::write(&file, "fn a() {\n\tx();\n}\nfn b() {\n\tx();\n}\n").unwrap();
+		let mut tissue_box = test_box();
+		let command = cli::Command::Ref(cli::Ref {
+			index: 0,
+			reference: Some(format!("{}:5", file.display()).parse().unwrap()),
+		});
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		// Both calls to x() look the same, so the neighbouring lines decide which one is followed.
+		std::fs::write(&file, "// header\n\nfn a() {\n\tx();\n}\nfn b() {\n\tx();\n}\n").unwrap();
+		let command = cli::Command::Ref(cli::Ref { index: 0, reference: None });
+		assert_eq!(cli::run(command, &mut tissue_box).unwrap().unwrap(), format!("0. {}:7\n", file.display()));
+
+		// A closing brace still at the old line number isn't necessarily the one referenced.
+		let command = cli::Command::Ref(cli::Ref {
+			index: 1,
+			reference: Some(format!("{}:8", file.display()).parse().unwrap()),
+		});
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		std::fs::write(&file, "// header\n\nfn a() {\n\tx();\n}\nfn c() {\n\ty();\n}\nfn b() {\n\tx();\n}\n").unwrap();
+		let command = cli::Command::Ref(cli::Ref { index: 1, reference: None });
+		assert_eq!(cli::run(command, &mut tissue_box).unwrap().unwrap(), format!("0. {}:11\n", file.display()));
+	}
+
+	#[cfg(unix)]
+	/// Write a shell script standing in for a command line tool, which logs its arguments to `log`.
+	fn fake_program(dir: &std::path::Path, name: &str, script: &str) -> std::path::PathBuf {
+		use std::os::unix::fs::PermissionsExt;
+		let path = dir.join(name);
+		std::fs::write(&path, format!("#!/bin/sh\necho \"$@\" >> '{}/log'\n{script}", dir.display())).unwrap();
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+		path
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn import_github() {
+		const ISSUE: &str = r#"{"number":7,"url":"https://github.com/evie-calico/tissuebox/issues/7","title":"Baz","body":"- [ ] Implement using abc\r\n- [x] Remove xyz\n\n---\n_Published from a tissue box._","labels":[{"name":"bug"}]}"#;
+		let dir = test_dir("import-github");
+		let gh = fake_program(&dir, "gh", &format!("case \"$2\" in\nview) printf '%s' '{ISSUE}';;\nlist) printf '%s' '[{ISSUE}]';;\nesac\n"));
+		let mut tissue_box = test_box();
+		tissue_box.config_mut().publish.github.program = gh;
+		let command = cli::Command::Import(cli::Import::Gh(cli::GhImport {
+			query: Some("7".into()),
+			assigned_to_me: false,
+			limit: 100,
+		}));
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		let tissue = tissue_box.get(2).unwrap();
+		assert_eq!(tissue.title, "Baz");
+		// Completed tasks are imported checked off.
+		assert_eq!(tissue.description, [Item { text: "Implement using abc".into(), done: false }, Item { text: "Remove xyz".into(), done: true }]);
+		assert!(tissue.tags.contains("bug"));
+		assert_eq!(tissue.issue.as_ref().unwrap().number, Some(7));
+
+		// The link is remembered, so importing again does nothing.
+		let command = cli::Command::Import(cli::Import::Gh(cli::GhImport {
+			query: Some("7".into()),
+			assigned_to_me: false,
+			limit: 100,
+		}));
+		assert!(cli::run(command, &mut tissue_box).is_err());
+		let command = cli::Command::Import(cli::Import::Gh(cli::GhImport { query: None, assigned_to_me: true, limit: 5 }));
+		assert!(cli::run(command, &mut tissue_box).unwrap().unwrap().starts_with("skipped:"));
+		assert!(tissue_box.get(3).is_none());
+		assert!(std::fs::read_to_string(dir.join("log")).unwrap().ends_with("--limit 5 --assignee @me\n"));
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn sync_github() {
+		const URL: &str = "https://github.com/evie-calico/tissuebox/issues/2";
+		let dir = test_dir("sync-github");
+		let issue = |title: &str, labels: &[&str], state: &str| {
+			let labels = labels.iter().map(|label| format!(r#"{{"name":"{label}"}}"#)).collect::<Vec<_>>().join(",");
+			format!(r#"{{"number":2,"url":"{URL}","title":"{title}","body":"","labels":[{labels}],"state":"{state}"}}"#)
+		};
+		std::fs::write(dir.join("issue.json"), issue("Bar", &["good first issue", "help wanted", "bug"], "OPEN")).unwrap();
+		let gh = fake_program(&dir, "gh", &format!("case \"$2\" in\nview) cat '{}/issue.json';;\nesac\n", dir.display()));
+		let mut tissue_box = test_box();
+		tissue_box.config_mut().publish.github.program = gh;
+		tissue_box.get_mut(1).unwrap().link(Issue {
+			url: URL.into(),
+			number: Some(2),
+			backend: tissuebox::publish::Backend::Github,
+		});
+
+		let tissue = tissue_box.get_mut(1).unwrap();
+		tissue.describe("Document it".into());
+		tissue.tags.remove("help wanted");
+		tissue.tag("docs".into());
+		let out = cli::run(cli::Command::Sync(cli::Sync { to: None }), &mut tissue_box).unwrap().unwrap();
+		assert_eq!(
+			out,
+			format!("{URL}: commented with 1 new description\n{URL}: labeled the issue \"docs\"\n{URL}: unlabeled the issue \"help wanted\"\n{URL}: tagged \"bug\"\n")
+		);
+		let log = std::fs::read_to_string(dir.join("log")).unwrap();
+		assert!(log.contains(&format!("issue comment {URL} --body - [ ] Document it\n")));
+		assert!(log.contains("label create docs\n"));
+		assert!(log.ends_with(&format!("issue edit {URL} --add-label docs --remove-label help wanted\n")));
+		assert!(tissue_box.get(1).unwrap().tags.contains("bug"));
+
+		// Nothing changed since, so syncing again does nothing.
+		std::fs::write(dir.join("issue.json"), issue("Bar", &["good first issue", "bug", "docs"], "OPEN")).unwrap();
+		assert_eq!(cli::run(cli::Command::Sync(cli::Sync { to: None }), &mut tissue_box).unwrap().unwrap(), "");
+		// Issues on other trackers are left alone.
+		tissue_box.get_mut(1).unwrap().title = "Renamed".into();
+		let to = Some(tissuebox::publish::Backend::Local);
+		assert_eq!(cli::run(cli::Command::Sync(cli::Sync { to }), &mut tissue_box).unwrap().unwrap(), "");
+		tissue_box.get_mut(1).unwrap().title = "Bar".into();
+
+		// Swapping one line for another keeps the count the same, but the new line is still posted.
+		let tissue = tissue_box.get_mut(1).unwrap();
+		tissue.description.remove(0);
+		tissue.describe("Test it".into());
+		assert_eq!(cli::run(cli::Command::Sync(cli::Sync { to: None }), &mut tissue_box).unwrap().unwrap(), format!("{URL}: commented with 1 new description\n"));
+		assert!(std::fs::read_to_string(dir.join("log")).unwrap().contains(&format!("issue comment {URL} --body - [ ] Test it\n")));
+
+		// Renaming both sides is a conflict, and neither title is overwritten.
+		std::fs::write(dir.join("issue.json"), issue("Baz", &["good first issue", "bug", "docs"], "OPEN")).unwrap();
+		tissue_box.get_mut(1).unwrap().title = "Qux".into();
+		assert!(cli::run(cli::Command::Sync(cli::Sync { to: None }), &mut tissue_box).unwrap().unwrap().contains("conflict"));
+		assert_eq!(tissue_box.get(1).unwrap().title, "Qux");
+
+		// Closing the issue closes the tissue.
+		tissue_box.get_mut(1).unwrap().title = "Baz".into();
+		std::fs::write(dir.join("issue.json"), issue("Baz", &["good first issue", "bug", "docs"], "CLOSED")).unwrap();
+		assert!(cli::run(cli::Command::Sync(cli::Sync { to: None }), &mut tissue_box).unwrap().unwrap().ends_with("closed, since the issue was\n"));
+		assert!(tissue_box.get(1).is_none());
+		assert_eq!(tissue_box.get_removed(0).unwrap().resolution, Some(Resolution::Published { url: URL.into(), number: Some(2) }));
+	}
+
+	#[test]
+	fn priority_sort() {
+		let mut tissue_box = test_box();
+		tissue_box.create("Baz".into());
+		let command = cli::Command::Priority(cli::SetPriority { index: 2, level: "p1".parse().unwrap() });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		let command = cli::Command::Priority(cli::SetPriority { index: 1, level: "P0".parse().unwrap() });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		let order = |tissue_box: &mut TissueBox, sort| {
+			let command = cli::Command::List(cli::List { sort: Some(sort), ..Default::default() });
+			cli::run(command, tissue_box).unwrap().unwrap().lines().filter_map(|line| line.split_once(". ")).map(|(index, _)| index.to_string()).collect::<Vec<_>>()
+		};
+		assert_eq!(order(&mut tissue_box, Sort::Priority), ["1", "2", "0"]);
+		assert_eq!(order(&mut tissue_box, Sort::Title), ["1", "2", "0"]);
+		// Sorting a listing leaves the tissues where they were.
+		assert_eq!(tissue_box.get(0).unwrap().title, "Foo");
+
+		let command = cli::Command::Priority(cli::SetPriority { index: 1, level: "none".parse().unwrap() });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		assert_eq!(tissue_box.get(1).unwrap().priority, None);
+		assert_eq!(Priority::raise(None), Some(Priority::P3));
+		assert_eq!(Priority::raise(Some(Priority::P0)), Some(Priority::P0));
+		assert_eq!(Priority::lower(Some(Priority::P3)), None);
+	}
+
+	#[test]
+	fn check_items() {
+		let path = test_dir("check-items").join("tissuebox");
+		let mut tissue_box = test_box();
+		let command = cli::Command::Check(cli::Check { index: 1, item: 1, uncheck: false });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		assert!(cli::run(cli::Command::List(cli::List { index: Some(1), ..Default::default() }), &mut tissue_box).unwrap().unwrap().starts_with("Bar [1/2]"));
+		let command = cli::Command::Check(cli::Check { index: 1, item: 2, uncheck: false });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::DescriptionNotFound(1, 2))));
+
+		// Checked items survive a round trip, and unchecked ones are still stored as plain strings.
+		tissue_box.save(&path).unwrap();
+		assert!(std::fs::read_to_string(&path).unwrap().contains(r#"description = ["Implement using abc", { text = "Remove xyz", done = true }]"#));
+		let mut tissue_box = TissueBox::open(&path).unwrap();
+		assert_eq!(tissue_box.get(1).unwrap().progress(), (1, 2));
+
+		tissue_box.config_mut().require_checked = true;
+		let command = cli::Command::Commit(cli::Commit { index: 1, force: false });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::UncheckedItems(1, 1))));
+		// Finishing a branch is held to the same rule, before anything is checked out.
+		tissue_box.get_mut(1).unwrap().branch = Some(tissuebox::Branch { name: "bar".into(), base: "main".into() });
+		let command = cli::Command::Finish(cli::Finish { index: 1, merge: false, force: false });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::UncheckedItems(1, 1))));
+		let command = cli::Command::Check(cli::Check { index: 1, item: 1, uncheck: true });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		assert_eq!(tissue_box.get(1).unwrap().progress(), (0, 2));
+	}
+
+	#[test]
+	fn split_subtasks() {
+		let path = test_dir("split-subtasks").join("tissuebox");
+		let mut tissue_box = test_box();
+		assert!(cli::run(cli::Command::Split(cli::Index { index: 1 }), &mut tissue_box).is_ok());
+		let bar = tissue_box.get(1).unwrap().id;
+		assert!(tissue_box.get(1).unwrap().description.is_empty());
+		assert_eq!(tissue_box.get(2).unwrap().title, "Implement using abc");
+		assert_eq!(tissue_box.get(3).unwrap().parent, Some(bar));
+		let listed = cli::run(cli::Command::List(cli::List::default()), &mut tissue_box).unwrap().unwrap();
+		assert!(listed.contains("1. Bar [0/2]"));
+		assert!(listed.contains("\n  2. Implement using abc\n  3. Remove xyz"));
+
+		// Closed subtasks still count towards their parent's progress, unless they were discarded.
+		assert!(tissue_box.close(2, Resolution::Committed { hash: "abc1234".into() }).is_some());
+		assert_eq!(tissue_box.progress(1), (1, 2));
+		assert!(tissue_box.close(2, Resolution::Discarded).is_some());
+		assert_eq!(tissue_box.progress(1), (1, 1));
+
+		// Tissues saved before they had IDs are given them when opened.
+		std::fs::write(&path, "[[tissues]]\ntitle = \"Foo\"\n\n[[tissues]]\ntitle = \"Bar\"\n").unwrap();
+		let mut tissue_box = TissueBox::open(&path).unwrap();
+		assert_eq!((tissue_box.get(0).unwrap().id, tissue_box.get(1).unwrap().id), (1, 2));
+		tissue_box.create("Baz".into());
+		assert_eq!(tissue_box.get(2).unwrap().id, 3);
+	}
+
+	#[test]
+	fn dependencies() {
+		let mut tissue_box = test_box();
+		let depend = |index, on, remove| cli::Command::Depend(cli::Depend { index, on, remove });
+		assert!(cli::run(depend(0, 1, false), &mut tissue_box).is_ok());
+		assert!(matches!(cli::run(depend(1, 0, false), &mut tissue_box), Err(cli::Error::DependencyCycle(0, 1))));
+		assert!(matches!(cli::run(depend(1, 1, false), &mut tissue_box), Err(cli::Error::DependencyCycle(1, 1))));
+
+		let ready = cli::Filter { ready: true, ..Default::default() };
+		let listed = |tissue_box: &mut TissueBox, filter| cli::run(cli::Command::List(cli::List { filter, ..Default::default() }), tissue_box).unwrap().unwrap();
+		assert!(listed(&mut tissue_box, Default::default()).contains("  < blocked by 1\n"));
+		assert!(listed(&mut tissue_box, ready).starts_with("1. Bar"));
+
+		// Closing the blocker unblocks the tissue, and restoring it blocks it again.
+		assert!(tissue_box.close(1, Resolution::Discarded).is_some());
+		assert!(tissue_box.blockers(0).is_empty());
+		assert!(tissue_box.restore(0).is_some());
+		assert_eq!(tissue_box.blockers(0), [1]);
+
+		assert!(cli::run(depend(0, 1, true), &mut tissue_box).is_ok());
+		assert!(matches!(cli::run(depend(0, 1, true), &mut tissue_box), Err(cli::Error::DependencyNotFound(0, 1))));
+	}
+
+	#[test]
+	fn workflow_status() {
+		let mut tissue_box = test_box();
+		let status = |index: usize, status: &str| {
+			cli::Command::Status(cli::SetStatus {
+				status: status.into(),
+				targets: targets(&index.to_string()),
+			})
+		};
+		assert!(!tissue_box.to_string().contains(" | "));
+		assert!(cli::run(status(0, "in progress"), &mut tissue_box).is_ok());
+		let listed = tissue_box.to_string();
+		assert!(listed.contains("0. in progress | Foo"));
+		assert!(listed.contains("1. todo        | Bar"));
+		assert!(matches!(cli::run(status(0, "bogus"), &mut tissue_box), Err(cli::Error::StatusFailed(workflow::Error::Unknown(..)))));
+		// The index comes first, but can be left out when querying.
+		let parsed = |args: &[&str]| match Cli::try_parse_from(["tissue", "status"].iter().chain(args)).map(|cli| cli.command) {
+			Ok(Some(cli::Command::Status(cli::SetStatus { targets, status }))) => (targets.indices.is_some(), targets.queries.len(), status),
+			_ => panic!("{args:?} didn't parse as a status command"),
+		};
+		assert_eq!(parsed(&["1", "review"]), (true, 0, "review".into()));
+		assert_eq!(parsed(&["review", "--where", "tag:bug"]), (false, 1, "review".into()));
+
+		let workflow = &mut tissue_box.config_mut().workflow;
+		workflow.transitions.insert("todo".into(), vec!["in progress".into()]);
+		assert_eq!(workflow.cycle(Some("todo"), false), Some("in progress"));
+		assert_eq!(workflow.cycle(Some("todo"), true), Some("in progress"));
+		assert!(matches!(cli::run(status(1, "done"), &mut tissue_box), Err(cli::Error::StatusFailed(workflow::Error::NotAllowed { .. }))));
+
+		// With history kept, committed tissues stay in the box until moved out of the last status.
+		tissue_box.config_mut().workflow.keep_history = true;
+		assert!(tissue_box.close_committed(0, "abc1234".into()).is_some());
+		assert_eq!(tissue_box.status(0), Some("done"));
+		assert_eq!(tissue_box.get(0).unwrap().resolution, Some(Resolution::Committed { hash: "abc1234".into() }));
+		let command = cli::Command::Commit(cli::Commit { index: 0, force: false });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::AlreadyCommitted(0))));
+		// Its commit message no longer counts as referencing an open tissue.
+		let file = test_dir("workflow-commit-msg").join("COMMIT_EDITMSG");
+		std::fs::write(&file, "Foo\n").unwrap();
+		assert!(!tissuebox::hooks::references_tissue(&tissue_box, &file).unwrap());
+		assert!(cli::run(status(0, "review"), &mut tissue_box).is_ok());
+		assert_eq!(tissue_box.get(0).unwrap().resolution, None);
+	}
+
+	#[test]
+	fn time_tracking() {
+		use chrono::{TimeDelta, Utc};
+		use tissuebox::Interval;
+		let mut tissue_box = test_box();
+		let start = |index| cli::Command::Start(cli::Start { index, no_branch: true });
+		assert_eq!(cli::run(start(0), &mut tissue_box).unwrap(), None);
+		assert_eq!(tissue_box.running(), Some(0));
+		assert_eq!(cli::run(start(1), &mut tissue_box).unwrap().as_deref(), Some("stopped timing tissue 0\n"));
+		assert_eq!(tissue_box.starred().unwrap().title, "Bar");
+		assert!(cli::run(cli::Command::Stop, &mut tissue_box).unwrap().unwrap().starts_with("1. Bar"));
+		assert!(matches!(cli::run(cli::Command::Stop, &mut tissue_box), Err(cli::Error::NoTimer)));
+		// Timing alone makes no branch, and a tissue never gets two.
+		assert!(tissue_box.get(0).unwrap().branch.is_none());
+		tissue_box.get_mut(0).unwrap().branch = Some(tissuebox::Branch { name: "foo".into(), base: "main".into() });
+		assert!(matches!(cli::run(cli::Command::Start(cli::Start { index: 0, no_branch: false }), &mut tissue_box), Err(cli::Error::BranchExists(0))));
+		assert_eq!(tissue_box.running(), None);
+
+		let now = Utc::now();
+		tissue_box.get_mut(0).unwrap().work = vec![Interval {
+			start: now - TimeDelta::hours(2),
+			end: Some(now - TimeDelta::hours(1)),
+		}];
+		tissue_box.get_mut(1).unwrap().work = vec![Interval {
+			start: now - TimeDelta::days(10),
+			end: Some(now - TimeDelta::days(10) + TimeDelta::minutes(30)),
+		}];
+		let report = |since: Option<&str>| {
+			cli::Command::Report(cli::Report::Time(cli::TimeReport {
+				since: since.map(|since| since.parse().unwrap()),
+			}))
+		};
+		assert_eq!(cli::run(report(Some("1w")), &mut tissue_box).unwrap().unwrap(), "Tissues:\n        1h  Foo\nTags:\n        1h  bug\nTotal: 1h\n");
+		let all = cli::run(report(None), &mut tissue_box).unwrap().unwrap();
+		assert!(all.contains("       30m  help wanted\n"));
+		assert!(all.ends_with("Total: 1h 30m\n"));
+		assert_eq!(cli::run(report(Some("99999999w")), &mut tissue_box).unwrap().unwrap(), all);
+
+		assert_eq!(tissue_box.get(0).unwrap().commit_message(tissue_box.config()), "Foo");
+		tissue_box.config_mut().time_trailer = true;
+		assert_eq!(tissue_box.get(0).unwrap().commit_message(tissue_box.config()), "Foo\n\nTime-spent: 1h");
+
+		// Closing a tissue stops its timer.
+		assert_eq!(tissue_box.start_timer(0), Some(None));
+		assert!(tissue_box.close(0, Resolution::Discarded).is_some_and(|tissue| !tissue.is_running()));
+		assert_eq!(tissue_box.running(), None);
+	}
+
+	#[test]
+	fn estimates() {
+		use chrono::{TimeDelta, Utc};
+		use tissuebox::{Estimate, Oversized, Size};
+		let path = test_dir("estimates").join("tissuebox");
+		assert_eq!("90m".parse(), Ok(Estimate::Minutes(90)));
+		assert_eq!("xl".parse(), Ok(Estimate::Size(Size::XL)));
+		assert!("soon".parse::<Estimate>().is_err());
+
+		let mut tissue_box = test_box();
+		let estimate = |index, estimate: &str| cli::Command::Estimate(cli::SetEstimate { index, estimate: estimate.parse().unwrap() });
+		assert!(cli::run(estimate(0, "3h"), &mut tissue_box).is_ok());
+		assert!(cli::run(estimate(1, "M"), &mut tissue_box).is_ok());
+		assert!(tissue_box.to_string().contains("0. Foo [0/1] ~3h (bug)"));
+		let too_big = cli::Filter { too_big: true, ..Default::default() };
+		let listed = |tissue_box: &mut TissueBox, filter| cli::run(cli::Command::List(cli::List { filter, ..Default::default() }), tissue_box).unwrap().unwrap();
+		assert!(!listed(&mut tissue_box, too_big).contains("1. Bar"));
+
+		// Long descriptions and old tissues are too big no matter the estimate.
+		tissue_box.config_mut().too_big.descriptions = 1;
+		assert!(listed(&mut tissue_box, cli::Filter { too_big: true, ..Default::default() }).contains("1. Bar"));
+		let now = Utc::now();
+		let bar = tissue_box.get_mut(1).unwrap();
+		bar.created = Some(now - TimeDelta::days(20));
+		assert_eq!(bar.oversized(&Default::default(), now), [Oversized::Age(20)]);
+		bar.link(Issue {
+			url: "https://github.com/evie-calico/tissuebox/issues/2".into(),
+			number: Some(2),
+			backend: tissuebox::publish::Backend::Github,
+		});
+		assert!(bar.oversized(&Default::default(), now).is_empty());
+		assert!(!listed(&mut tissue_box, cli::Filter { too_big: true, ..Default::default() }).contains("1. Bar"));
+		tissue_box.get_mut(1).unwrap().issue = None;
+
+		tissue_box.save(&path).unwrap();
+		let saved = std::fs::read_to_string(&path).unwrap();
+		assert!(saved.contains("estimate = 180\n"));
+		assert!(saved.contains("estimate = \"M\"\n"));
+		assert_eq!(TissueBox::open(&path).unwrap().get(1).unwrap().estimate, Some(Estimate::Size(Size::M)));
+	}
+
+	#[test]
+	fn notes() {
+		let path = test_dir("notes").join("tissuebox");
+		let mut tissue_box = test_box();
+		let print = |index| cli::Command::Notes(cli::Notes { index, print: true });
+		assert_eq!(cli::run(print(0), &mut tissue_box).unwrap(), None);
+		tissue_box.get_mut(0).unwrap().notes = "# Plan\n\n```rust\nlet bar = Bar::new();\n```".into();
+		assert_eq!(cli::run(print(0), &mut tissue_box).unwrap().unwrap(), "# Plan\n\n```rust\nlet bar = Bar::new();\n```\n");
+		let shown = cli::run(cli::Command::Show(cli::Show { index: 0, trash: false }), &mut tissue_box).unwrap().unwrap();
+		assert!(shown.ends_with("  - [ ] Depends on Bar implementation\n\n# Plan\n\n```rust\nlet bar = Bar::new();\n```\n"));
+		// Notes stay out of listings.
+		assert!(!tissue_box.to_string().contains("# Plan"));
+
+		tissue_box.save(&path).unwrap();
+		assert_eq!(TissueBox::open(&path).unwrap().get(0).unwrap().notes, tissue_box.get(0).unwrap().notes);
+	}
+
+	#[test]
+	fn render_notes() {
+		let notes = "# Plan\n\n## Steps\n\n1. Write [the parser](https://example.com/parser)\n   - with *tests*\n   - [x] and docs\n2. Ship\n\n```rust\nlet bar = Bar::new();\nbar.run();\n```\n\n> Quoted `code`";
+		let text = tissuebox::tui::markdown(notes);
+		let lines = text.lines.iter().map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>()).collect::<Vec<_>>();
+		assert_eq!(
+			lines,
+			[
+				"# Plan",
+				"",
+				"## Steps",
+				"",
+				"1. Write the parser <https://example.com/parser>",
+				"  • with tests",
+				"  • [x] and docs",
+				"2. Ship",
+				"",
+				"  let bar = Bar::new();",
+				"  bar.run();",
+				"",
+				"│ Quoted code"
+			]
+		);
+		let style = |content: &str| text.lines.iter().flat_map(|line| &line.spans).find(|span| span.content == content).unwrap().style;
+		assert!(style("Plan").add_modifier.contains(ratatui::style::Modifier::BOLD));
+		assert!(style("the parser").add_modifier.contains(ratatui::style::Modifier::UNDERLINED));
+		assert!(style("tests").add_modifier.contains(ratatui::style::Modifier::ITALIC));
+		assert_eq!(style("code").fg, Some(ratatui::style::Color::Yellow));
+	}
+
+	#[test]
+	fn attachments() {
+		let dir = test_dir("attachments");
+		let path = dir.join("tissuebox");
+		let log = dir.join("trace.log");
+		std::fs::write(&log, "thread 'main' panicked").unwrap();
+		let mut tissue_box = test_box();
+		let attach = |index, target: &str| cli::Command::Attach(cli::Attach { index, target: target.into() });
+		// Files can't be copied anywhere until the box has a path.
+		assert!(matches!(cli::run(attach(0, log.to_str().unwrap()), &mut tissue_box), Err(cli::Error::AttachFailed(_))));
+		tissue_box.save(&path).unwrap();
+		assert!(cli::run(attach(0, "https://example.com/pull/1"), &mut tissue_box).is_ok());
+		assert!(cli::run(attach(0, log.to_str().unwrap()), &mut tissue_box).is_ok());
+		assert!(matches!(cli::run(attach(0, log.to_str().unwrap()), &mut tissue_box), Err(cli::Error::AttachFailed(e)) if e.kind() == std::io::ErrorKind::AlreadyExists));
+		let listed = cli::run(cli::Command::Attachments(cli::Attachments { index: 0, open: None }), &mut tissue_box).unwrap().unwrap();
+		assert_eq!(listed, "0. link https://example.com/pull/1\n1. file trace.log\n");
+		let copy = dir.join("tissuebox.d").join(tissue_box.get(0).unwrap().id.to_string()).join("trace.log");
+		assert_eq!(std::fs::read_to_string(&copy).unwrap(), "thread 'main' panicked");
+
+		tissue_box.config_mut().attachment_limit = 4;
+		assert!(matches!(cli::run(attach(1, log.to_str().unwrap()), &mut tissue_box), Err(cli::Error::AttachFailed(_))));
+
+		// Attachments follow the tissue into the recycle bin and are deleted with it.
+		assert!(tissue_box.remove(0).is_some());
+		assert_eq!(tissue_box.get_removed(0).unwrap().attachments.len(), 2);
+		assert!(copy.exists());
+		assert!(matches!(cli::run(cli::Command::Purge(cli::OptionIndex { index: Some(1) }), &mut tissue_box), Err(cli::Error::RemovedTissueNotFound(1))));
+		// A tissue whose attachments can't be deleted stays in the recycle bin, while the others are purged.
+		assert!(tissue_box.remove(0).is_some());
+		std::fs::write(dir.join("tissuebox.d").join(tissue_box.get_removed(1).unwrap().id.to_string()), "").unwrap();
+		assert!(matches!(cli::run(cli::Command::Purge(cli::OptionIndex { index: None }), &mut tissue_box), Err(cli::Error::PartlyPurged(1, _))));
+		assert!(!copy.exists());
+		assert_eq!(tissue_box.get_removed(0).unwrap().title, "Bar");
+		assert!(tissue_box.get_removed(1).is_none());
+	}
+
+	#[test]
+	fn tag_management() {
+		let mut tissue_box = test_box();
+		tissue_box.get_mut(0).unwrap().tag("help wanted".into());
+		let tags = |action| cli::Command::Tags(cli::Tags { action });
+		let describe = cli::TagAction::Describe(cli::DescribeTag {
+			tag: "bug".into(),
+			description: Some("Something isn't working".into()),
+		});
+		assert!(cli::run(tags(Some(describe)), &mut tissue_box).is_ok());
+		assert_eq!(cli::run(tags(None), &mut tissue_box).unwrap().unwrap(), "bug (1): Something isn't working\ngood first issue (1)\nhelp wanted (2)\n");
+
+		let rename = |old: &str, new: &str| tags(Some(cli::TagAction::Rename(cli::RenameTag { old: old.into(), new: new.into() })));
+		assert!(matches!(cli::run(rename("bug", "help wanted"), &mut tissue_box), Err(cli::Error::TagExists(_))));
+		assert!(matches!(cli::run(rename("feature", "enhancement"), &mut tissue_box), Err(cli::Error::UnusedTag(_))));
+		assert!(matches!(cli::run(rename("bug", "bug, urgent"), &mut tissue_box), Err(cli::Error::InvalidTag(_))));
+		assert!(cli::run(rename("bug", "defect"), &mut tissue_box).is_ok());
+		assert_eq!(tissue_box.tag_info("defect").unwrap().description.as_deref(), Some("Something isn't working"));
+		// Tags that differ only by case would be one label on GitHub, unless one replaces the other.
+		let command = cli::Command::Tag(cli::Tag { tag: "Defect".into(), targets: targets("1") });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::InvalidTag(_))));
+		assert!(cli::run(rename("defect", "Defect"), &mut tissue_box).is_ok());
+		assert!(cli::run(rename("Defect", "defect"), &mut tissue_box).is_ok());
+		// Tags only in the recycle bin or only described are still known.
+		tissue_box.create("Baz".into());
+		tissue_box.get_mut(2).unwrap().tag("wontfix".into());
+		assert!(tissue_box.remove(2).is_some());
+		assert!(matches!(cli::run(rename("defect", "wontfix"), &mut tissue_box), Err(cli::Error::TagExists(_))));
+		let describe = cli::TagAction::Describe(cli::DescribeTag {
+			tag: "feature".into(),
+			description: Some("New functionality".into()),
+		});
+		assert!(cli::run(tags(Some(describe)), &mut tissue_box).is_ok());
+		assert_eq!(cli::run(rename("feature", "enhancement"), &mut tissue_box).unwrap().as_deref(), Some("renamed feature to enhancement on 0 tissues\n"));
+		assert_eq!(tissue_box.tag_info("enhancement").unwrap().description.as_deref(), Some("New functionality"));
+		assert!(tissue_box.tag_info("feature").is_none());
+		tissue_box.set_tag_info("enhancement", TagInfo::default());
+
+		let merge = cli::TagAction::Merge(cli::MergeTags {
+			tags: vec!["good first issue".into(), "help wanted".into()],
+			into: "easy".into(),
+		});
+		assert_eq!(cli::run(tags(Some(merge)), &mut tissue_box).unwrap().as_deref(), Some("merged into easy on 2 tissues\n"));
+		assert_eq!(cli::run(tags(None), &mut tissue_box).unwrap().unwrap(), "defect (1): Something isn't working\neasy (2)\n");
+
+		let color = |color: &str| tags(Some(cli::TagAction::Color(cli::ColorTag { tag: "easy".into(), color: Some(color.into()) })));
+		assert!(matches!(cli::run(color("sparkly"), &mut tissue_box), Err(cli::Error::InvalidColor(_))));
+		assert!(cli::run(color("#0e8a16"), &mut tissue_box).is_ok());
+
+		// Tags that can't round-trip through gh are refused before publishing.
+		tissue_box.get_mut(0).unwrap().tag(" spaced ".into());
+		assert!(matches!(tissue_box.config().publish.plan(tissue_box.get(0).unwrap(), None), Err(publish::Error::InvalidLabel(_))));
+		let command = cli::Command::Tag(cli::Tag { tag: "x".repeat(51), targets: targets("0") });
+		assert!(matches!(cli::run(command, &mut tissue_box), Err(cli::Error::InvalidTag(_))));
+	}
+
+	#[test]
+	fn tag_completion() {
+		use tissuebox::tui::{fuzzy_score, tag_completions};
+		assert_eq!(fuzzy_score("HW", "help wanted"), Some((false, 5)));
+		assert_eq!(fuzzy_score("gfi", "good first issue"), Some((false, 6)));
+		assert_eq!(fuzzy_score("x", "bug"), None);
+		// Prefixes come first, then the closest matches.
+		assert!(fuzzy_score("bu", "bug") < fuzzy_score("bu", "rebuild"));
+		assert!(fuzzy_score("ui", "ui") < fuzzy_score("ui", "build"));
+
+		let mut tissue_box = test_box();
+		tissue_box.get_mut(1).unwrap().tag("Blocker".into());
+		// Tags the tissue already has aren't offered.
+		assert_eq!(tag_completions(&tissue_box, 0, "B"), ["Blocker"]);
+		assert_eq!(tag_completions(&tissue_box, 0, "E"), ["Blocker", "good first issue", "help wanted"]);
+		assert_eq!(tag_completions(&tissue_box, 1, ""), ["bug"]);
+	}
+
+	#[test]
+	fn tag_order() {
+		let mut tissue_box = test_box();
+		for tag in ["wontfix", "api", "help wanted"] {
+			tissue_box.get_mut(1).unwrap().tag(tag.into());
+		}
+		assert_eq!(tissue_box.get(1).unwrap().to_string().lines().next(), Some("Bar [0/2] (api, good first issue, help wanted, wontfix)"));
+		assert_eq!(publish::Plan::new(tissue_box.get(1).unwrap(), &tissue_box.config().publish).labels, ["api", "good first issue", "help wanted", "wontfix"]);
+
+		// Boxes saved with tags in any order are read sorted, and saved that way from then on.
+		let path = test_dir("tag-order").join("tissues.toml");
+		std::fs::write(&path, "[[tissues]]\ntitle = \"Foo\"\ntags = [\"zebra\", \"apple\"]\n").unwrap();
+		let mut tissue_box = TissueBox::open(&path).unwrap();
+		tissue_box.save(&path).unwrap();
+		assert!(std::fs::read_to_string(&path).unwrap().contains("tags = [\"apple\", \"zebra\"]"));
+	}
+
+	#[test]
+	fn bulk_operations() {
+		use tissuebox::select::{Indices, Query};
+		assert_eq!("1-4,7,2".parse::<Indices>().unwrap().expand(8), Ok(vec![1, 2, 3, 4, 7]));
+		assert!("4-1".parse::<Indices>().is_err());
+		assert_eq!("1,0-99999999999".parse::<Indices>().unwrap().expand(8), Err(8));
+		assert_eq!("tag:help wanted".parse::<Query>().unwrap(), Query::Tag("help wanted".into()));
+		assert!("owner:me".parse::<Query>().is_err());
+
+		let mut tissue_box = test_box();
+		for title in ["Baz", "Qux", "Quux"] {
+			tissue_box.create(title.into());
+		}
+		tissue_box.star(Some(4));
+		let tag = |tag: &str, targets| cli::Command::Tag(cli::Tag { tag: tag.into(), targets });
+		assert!(cli::run(tag("ui", targets("1-3")), &mut tissue_box).is_ok());
+		let bugs = || cli::Targets {
+			indices: None,
+			queries: vec!["tag:bug".parse().unwrap()],
+		};
+		assert!(cli::run(tag("ui", bugs()), &mut tissue_box).is_ok());
+		assert_eq!((0..5).filter(|&i| tissue_box.get(i).unwrap().tags.contains("ui")).count(), 4);
+
+		// Nothing changes unless every targeted tissue can be changed.
+		let status = |targets| cli::Command::Status(cli::SetStatus { status: "review".into(), targets });
+		tissue_box.config_mut().workflow.transitions.insert("waiting".into(), vec!["todo".into()]);
+		assert!(tissue_box.set_status(3, "waiting").is_ok());
+		assert!(matches!(cli::run(status(targets("0-4")), &mut tissue_box), Err(cli::Error::StatusFailed(_))));
+		assert_eq!(tissue_box.status(0), Some("todo"));
+		assert!(matches!(cli::run(status(targets("2-5")), &mut tissue_box), Err(cli::Error::TissueNotFound(5))));
+		let untag = |targets| {
+			cli::Command::Remove(cli::Remove {
+				targets,
+				which: Some(cli::WhichRemove::Tag(cli::TagName { tag: "ui".into() })),
+			})
+		};
+		assert!(matches!(cli::run(untag(targets("0-4")), &mut tissue_box), Err(cli::Error::TagNotFound(4, _))));
+		assert!(tissue_box.get(0).unwrap().tags.contains("ui"));
+
+		assert!(cli::run(status(targets("0-2")), &mut tissue_box).is_ok());
+		let reviewing = cli::Targets {
+			indices: Some(targets("1-4").indices.unwrap()),
+			queries: vec!["status:review".parse().unwrap()],
+		};
+		assert!(cli::run(untag(reviewing), &mut tissue_box).is_ok());
+		assert_eq!((0..5).filter(|&i| tissue_box.get(i).unwrap().tags.contains("ui")).count(), 2);
+		assert!(matches!(
+			cli::run(
+				tag(
+					"ui",
+					cli::Targets {
+						indices: None,
+						queries: vec![Query::Priority(Some(Priority::P0))]
+					}
+				),
+				&mut tissue_box
+			),
+			Err(cli::Error::NoMatches)
+		));
+
+		// Removing several tissues keeps the star on the same tissue.
+		assert!(cli::run(cli::Command::Remove(cli::Remove { targets: targets("0,2"), which: None }), &mut tissue_box).is_ok());
+		assert_eq!((0..3).map(|i| tissue_box.get(i).unwrap().title.as_str()).collect::<Vec<_>>(), ["Bar", "Qux", "Quux"]);
+		assert_eq!(tissue_box.starred().map(|tissue| tissue.title.as_str()), Some("Quux"));
+		assert!(matches!(cli::run(cli::Command::Remove(cli::Remove { targets: Default::default(), which: None }), &mut tissue_box), Err(cli::Error::NoTargets)));
+	}
+
+	#[test]
+	fn due_dates() {
+		use chrono::NaiveDate;
+		use tissuebox::time::parse_date;
+		// A Sunday.
+		let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+		assert_eq!(parse_date("tomorrow", today), Ok(NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()));
+		assert_eq!(parse_date("fri", today), Ok(NaiveDate::from_ymd_opt(2026, 10, 23).unwrap()));
+		assert_eq!(parse_date("Sunday", today), Ok(NaiveDate::from_ymd_opt(2026, 10, 25).unwrap()));
+		assert_eq!(parse_date("3d", today), Ok(NaiveDate::from_ymd_opt(2026, 10, 21).unwrap()));
+		assert_eq!(parse_date("2026-11-01", today), Ok(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap()));
+		assert!(parse_date("someday", today).is_err());
+		assert_eq!(parse_date("2w", today), Ok(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap()));
+		assert!(parse_date("90m", today).is_err());
+		assert!(parse_date("2h", today).is_err());
+		assert!(parse_date("99999999w", today).is_err());
+
+		let mut tissue_box = test_box();
+		assert!(cli::run(cli::Command::Remind, &mut tissue_box).unwrap().is_none());
+		tissue_box.get_mut(0).unwrap().due = Some(tissuebox::time::today() - chrono::Days::new(1));
+		let command = cli::Command::Due(cli::SetDue { index: 1, date: "2d".parse().unwrap() });
+		assert!(cli::run(command, &mut tissue_box).is_ok());
+		let listed = |tissue_box: &mut TissueBox, filter| cli::run(cli::Command::List(cli::List { filter, ..Default::default() }), tissue_box).unwrap().unwrap();
+		assert!(listed(&mut tissue_box, cli::Filter { overdue: true, ..Default::default() }).starts_with("0. Foo"));
+		assert!(!listed(&mut tissue_box, cli::Filter { overdue: true, ..Default::default() }).contains("1. Bar"));
+		let due_soon = listed(
+			&mut tissue_box,
+			cli::Filter {
+				due_within: Some("3d".parse().unwrap()),
+				..Default::default()
+			},
+		);
+		assert!(due_soon.contains("0. Foo") && due_soon.contains("1. Bar"));
+		// Spans past the last representable date include every due tissue.
+		let due_ever = listed(
+			&mut tissue_box,
+			cli::Filter {
+				due_within: Some("99999999w".parse().unwrap()),
+				..Default::default()
+			},
+		);
+		assert!(due_ever.contains("0. Foo") && due_ever.contains("1. Bar"));
+		assert!(matches!(cli::run(cli::Command::Remind, &mut tissue_box), Err(cli::Error::Overdue(overdue)) if overdue.starts_with("0. Foo")));
+	}
+
+	#[test]
+	fn save_marks_updated() {
+		let path = test_dir("save-marks-updated").join("tissuebox");
+		let mut tissue_box = test_box();
+		tissue_box.save(&path).unwrap();
+		let mut tissue_box = TissueBox::open(&path).unwrap();
+		let updated = tissue_box.get(0).unwrap().updated;
+		assert!(updated.is_some());
+		tissue_box.get_mut(1).unwrap().describe("Keep xyz after all".into());
+		std::thread::sleep(std::time::Duration::from_millis(10));
+		tissue_box.save(&path).unwrap();
+		assert_eq!(tissue_box.get(0).unwrap().updated, updated);
+		assert!(tissue_box.get(1).unwrap().updated > updated);
+	}
+
+	fn draft(tissue: &Tissue) -> Plan {
+		Plan::new(tissue, &Default::default())
+	}
+
+	fn publish_with(publisher: &dyn Publisher, tissue: &Tissue) -> Issue {
+		publisher.execute(&publisher.plan(draft(tissue)).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn publish_body() {
+		let mut tissue_box = test_box();
+		tissue_box.get_mut(1).unwrap().references.push("src/lib.rs:10".parse().unwrap());
+		assert_eq!(
+			draft(tissue_box.get(1).unwrap()).body,
+			"- [ ] Implement using abc\n- [ ] Remove xyz\n\n### References\n- `src/lib.rs:10`\n\n---\n_Published from a tissue box._\n"
+		);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn publish_github() {
+		let dir = test_dir("publish-github");
+		let gh = fake_program(
+			&dir,
+			"gh",
+			"case \"$1 $2\" in\n\"label list\") printf 'bug\\tSomething is broken\\n';;\n\"issue create\") echo https://github.com/evie-calico/tissuebox/issues/1;;\nesac\n",
+		);
+		let publisher = tissuebox::publish::GitHub { program: gh };
+		let plan = publisher.plan(draft(test_box().get(1).unwrap())).unwrap();
+		assert_eq!(plan.create_labels.len(), 2);
+		assert_eq!(plan.steps.len(), 3);
+
+		let mut tissue_box = test_box();
+		let tissue = tissue_box.get_mut(0).unwrap();
+		tissue.tag("help wanted".into());
+		tissue.metadata.assignees = vec!["evie-calico".into()];
+		tissue.metadata.issue_type = Some("Bug".into());
+		let issue = publish_with(&publisher, tissue_box.get(0).unwrap());
+		assert_eq!(
+			issue,
+			Issue {
+				url: "https://github.com/evie-calico/tissuebox/issues/1".into(),
+				number: Some(1),
+				backend: tissuebox::publish::Backend::Github,
+			}
+		);
+		let log = std::fs::read_to_string(dir.join("log")).unwrap();
+		// "bug" already exists, so only "help wanted" is created.
+		assert!(log.starts_with("label list --limit 1000\nlabel list --limit 1000\nlabel create help wanted\nissue create --title Foo --body"));
+		// Every label gets its own flag.
+		assert_eq!(log.matches("--label").count(), 2);
+		assert!(log.contains("--assignee evie-calico\n"));
+		let log = log.lines().collect::<Vec<_>>();
+		assert_eq!(log[log.len() - 1], "api --method PATCH repos/{owner}/{repo}/issues/1 --field type=Bug");
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn publish_rollback() {
+		let dir = test_dir("publish-rollback");
+		let gh = fake_program(&dir, "gh", "case \"$1 $2\" in\n\"issue create\") echo 'could not create issue' >&2; exit 1;;\nesac\n");
+		let publisher = tissuebox::publish::GitHub { program: gh };
+		let plan = publisher.plan(draft(test_box().get(1).unwrap())).unwrap();
+		let error = publisher.execute(&plan).unwrap_err();
+		assert!(matches!(error, tissuebox::publish::Error::CreateIssue(_)));
+		assert_eq!(error.to_string(), "failed to create issue: could not create issue");
+		// Both labels were created, so both are deleted again.
+		let log = std::fs::read_to_string(dir.join("log")).unwrap();
+		let (_, rolled_back) = log.split_once("issue create").unwrap();
+		assert!(rolled_back.contains("label delete help wanted --yes\n"));
+		assert!(rolled_back.contains("label delete good first issue --yes\n"));
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn publish_gitlab() {
+		let dir = test_dir("publish-gitlab");
+		let glab = fake_program(&dir, "glab", "echo Creating issue in evie-calico/tissuebox\necho https://gitlab.com/evie-calico/tissuebox/-/issues/1\n");
+		let publisher = tissuebox::publish::GitLab { program: glab };
+		let issue = publish_with(&publisher, test_box().get(0).unwrap());
+		assert_eq!(
+			issue,
+			Issue {
+				url: "https://gitlab.com/evie-calico/tissuebox/-/issues/1".into(),
+				number: Some(1),
+				backend: tissuebox::publish::Backend::Gitlab,
+			}
+		);
+		assert!(std::fs::read_to_string(dir.join("log")).unwrap().contains("--label bug"));
+	}
+
+	#[test]
+	fn publish_dry_run() {
+		let mut tissue_box = test_box();
+		let command = cli::Command::Publish(cli::Publish {
+			targets: targets("0"),
+			to: Some(tissuebox::publish::Backend::Local),
+			dry_run: true,
+		});
+		let out = cli::run(command, &mut tissue_box).unwrap().unwrap();
+		assert!(out.starts_with("Title: Foo\nLabels: bug\n"));
+		assert!(out.contains("  write issues/foo.md\n"));
+		assert_eq!(tissue_box.get(0).unwrap().title, "Foo");
+		assert!(!std::path::Path::new("issues").exists());
+	}
+
+	#[test]
+	fn publish_local() {
+		let dir = test_dir("publish-local");
+		let publisher = tissuebox::publish::Local { directory: dir.join("issues") };
+		let tissue_box = test_box();
+		let first = publish_with(&publisher, tissue_box.get(0).unwrap());
+		let second = publish_with(&publisher, tissue_box.get(0).unwrap());
+		assert_ne!(first, second);
+		assert_eq!(std::fs::read_to_string(first.url).unwrap(), "# Foo\n\nLabels: bug\n\n- [ ] Depends on Bar implementation\n\n---\n_Published from a tissue box._\n");
+	}
+
+	type Requests = std::sync::Arc<std::sync::Mutex<Vec<(String, String, String)>>>;
+
+	/// Serve canned Gitea API responses, recording each request's method, path and body.
+	fn fake_gitea() -> (String, Requests) {
+		use std::io::{BufRead, BufReader, Read, Write};
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+		let log = requests.clone();
+		std::thread::spawn(move || {
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				let mut reader = BufReader::new(stream.try_clone().unwrap());
+				let mut request_line = String::new();
+				reader.read_line(&mut request_line).unwrap();
+				let mut length = 0;
+				loop {
+					let mut header = String::new();
+					reader.read_line(&mut header).unwrap();
+					if header.trim().is_empty() {
+						break;
+					}
+					if let Some((name, value)) = header.split_once(':') {
+						if name.eq_ignore_ascii_case("content-length") {
+							length = value.trim().parse().unwrap();
+						}
+					}
+				}
+				let mut body = vec![0; length];
+				reader.read_exact(&mut body).unwrap();
+				let mut parts = request_line.split(' ');
+				let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+				let response = match (method.as_str(), path.split('?').next().unwrap()) {
+					("GET", "/api/v1/repos/evie/tissuebox/labels") if path.contains("page=1&") => r#"[{"id":1,"name":"help wanted"}]"#,
+					("GET", "/api/v1/repos/evie/tissuebox/labels") => "[]",
+					("POST", "/api/v1/repos/evie/tissuebox/labels") => r#"{"id":2,"name":"good first issue"}"#,
+					("POST", "/api/v1/repos/evie/tissuebox/issues") => r#"{"number":1,"html_url":"https://codeberg.org/evie/tissuebox/issues/1"}"#,
+					_ => "{}",
+				};
+				log.lock().unwrap().push((method, path, String::from_utf8(body).unwrap()));
+				write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}", response.len()).unwrap();
+			}
+		});
+		(url, requests)
+	}
+
+	#[test]
+	fn publish_gitea() {
+		let (url, requests) = fake_gitea();
+		let publisher = tissuebox::publish::Gitea {
+			url,
+			owner: "evie".into(),
+			repo: "tissuebox".into(),
+			token_env: "TISSUEBOX_TEST_TOKEN".into(),
+		};
+		let issue = publish_with(&publisher, test_box().get(1).unwrap());
+		assert_eq!(
+			issue,
+			Issue {
+				url: "https://codeberg.org/evie/tissuebox/issues/1".into(),
+				number: Some(1),
+				backend: tissuebox::publish::Backend::Gitea,
+			}
+		);
+		let requests = requests.lock().unwrap();
+		let created = requests
+			.iter()
+			.filter(|(method, _, _)| method == "POST")
+			.map(|(_, path, body)| (path.as_str(), serde_json::from_str::<serde_json::Value>(body).unwrap()))
+			.collect::<Vec<_>>();
+		assert_eq!(created[0], ("/api/v1/repos/evie/tissuebox/labels", serde_json::json!({ "name": "good first issue", "color": "#ededed" })));
+		assert_eq!(created[1].0, "/api/v1/repos/evie/tissuebox/issues");
+		assert!(created[1].1["body"].as_str().unwrap().starts_with("- [ ] Implement using abc\n- [ ] Remove xyz\n"));
+		let mut labels = created[1].1["labels"].as_array().unwrap().clone();
+		labels.sort_by_key(|id| id.as_u64());
+		assert_eq!(labels, [1, 2]);
+	}
 }
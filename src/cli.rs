@@ -1,6 +1,7 @@
 use crate::prelude::*;
+use chrono::{NaiveDate, TimeDelta, Utc};
 use clap::{Args, Parser, Subcommand};
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 #[derive(Parser)]
 pub struct Cli {
@@ -14,20 +15,75 @@ pub struct Cli {
 pub enum Command {
 	/// Display formatted tissuebox
 	List(List),
+	/// Display removed tissues and how they were resolved
+	Trash,
+	/// Permanently delete a removed tissue by index, or all of them, along with their attachments
+	Purge(OptionIndex),
+	/// Display a single tissue by index
+	Show(Show),
 	/// Create new tissue
 	Add(Add),
 	/// Append to an existing tissue's description by index
 	Describe(Describe),
-	/// Add a tag to an existing tissue by index
+	/// Add a tag to existing tissues by index or query
 	Tag(Tag),
+	/// List every tag with how many tissues use it, or manage tags across all tissues
+	Tags(Tags),
 	/// Edit the title of a tissue by index
 	Edit(Edit),
-	/// Delete an existing tissue by index
+	/// Delete existing tissues by index or query, or remove a description or tag from them
 	Remove(Remove),
 	/// Commit a tissue to git by index
-	Commit(Index),
-	/// Publish a tissue to GitHub by index
-	Publish(Index),
+	Commit(Commit),
+	/// Check off an item in a tissue's description by index
+	Check(Check),
+	/// Turn each unchecked item in a tissue's description into a subtask by index
+	Split(Index),
+	/// Make a tissue wait on another by index, so that it is blocked until the other is closed
+	Depend(Depend),
+	/// Move tissues to another workflow status by index or query
+	Status(SetStatus),
+	/// Set the priority of a tissue by index
+	Priority(SetPriority),
+	/// Estimate how much work a tissue is by index
+	Estimate(SetEstimate),
+	/// Set the due date of a tissue by index
+	Due(SetDue),
+	/// List overdue tissues, failing if there are any
+	Remind,
+	/// Publish tissues to an issue tracker by index or query
+	Publish(Publish),
+	/// Create tissues from issues on a tracker
+	#[command(subcommand)]
+	Import(Import),
+	/// Bring published and imported tissues up to date with their issues
+	Sync(Sync),
+	/// Set assignees, milestone, project or issue type used when publishing a tissue by index
+	Metadata(Metadata),
+	/// Start timing a tissue by index, star it, and create and check out a branch for it
+	Start(Start),
+	/// Stop timing the tissue whose timer is running
+	Stop,
+	/// Summarize work on tissues
+	#[command(subcommand)]
+	Report(Report),
+	/// Bring a tissue's branch back into its base branch and commit it by index
+	Finish(Finish),
+	/// Attach a path:line[:column] reference to a tissue by index, or list its references
+	Ref(Ref),
+	/// Open one of a tissue's references in $EDITOR by index
+	Open(Open),
+	/// Edit a tissue's markdown notes in $EDITOR by index
+	Notes(Notes),
+	/// Attach a URL or a copy of a small file to a tissue by index
+	Attach(Attach),
+	/// List a tissue's attachments by index, or open one of them
+	Attachments(Attachments),
+	/// Create tissues from TODO-style comments in the source code
+	Scan(Scan),
+	/// Manage git hooks
+	#[command(subcommand)]
+	Hooks(Hooks),
 }
 
 #[derive(Args)]
@@ -40,11 +96,73 @@ pub struct OptionIndex {
 	pub index: Option<usize>,
 }
 
-#[derive(Args)]
+/// Which tissues a command acts on, for commands that can act on several at once.
+#[derive(Args, Default)]
+pub struct Targets {
+	/// Indices of tissues, such as 3, 1-4 or 1-4,7
+	pub indices: Option<select::Indices>,
+	/// Only act on tissues matching a query such as tag:bug, status:review or priority:P1; repeat to narrow down further
+	#[arg(long = "where", value_name = "QUERY")]
+	pub queries: Vec<select::Query>,
+}
+
+impl Targets {
+	/// The indices of the targeted tissues in order, or `default` when neither indices nor queries were given.
+	///
+	/// Fails before anything is changed if an index is out of range or nothing is targeted,
+	/// so commands can act on every tissue or none.
+	fn select(&self, tissue_box: &TissueBox, default: Option<usize>) -> Result<Vec<usize>> {
+		let indices = match (&self.indices, default) {
+			(Some(indices), _) => indices.expand(tissue_box.tissues.len()).map_err(Error::TissueNotFound)?,
+			(None, _) if !self.queries.is_empty() => (0..tissue_box.tissues.len()).collect(),
+			(None, Some(default)) => vec![default],
+			(None, None) => return Err(Error::NoTargets),
+		};
+		let indices = indices.into_iter().filter(|&index| self.queries.iter().all(|query| query.matches(tissue_box, index))).collect::<Vec<_>>();
+		if indices.is_empty() {
+			return Err(Error::NoMatches);
+		}
+		Ok(indices)
+	}
+}
+
+#[derive(Args, Default)]
 pub struct List {
 	pub index: Option<usize>,
 	#[command(subcommand)]
 	pub which: Option<WhichList>,
+	/// Order to list tissues in, without changing the order they are stored in
+	#[arg(long)]
+	pub sort: Option<Sort>,
+	#[command(flatten)]
+	pub filter: Filter,
+}
+
+/// Narrows down which tissues are listed.
+#[derive(Args, Default)]
+pub struct Filter {
+	/// Only list tissues past their due date
+	#[arg(long)]
+	pub overdue: bool,
+	/// Only list tissues due within a length of time such as 3d, including overdue ones
+	#[arg(long, value_name = "SPAN")]
+	pub due_within: Option<time::Span>,
+	/// Only list tissues that aren't blocked by any open tissue
+	#[arg(long)]
+	pub ready: bool,
+	/// Only list tissues whose estimate, age or description is past the too_big config
+	#[arg(long)]
+	pub too_big: bool,
+}
+
+impl Filter {
+	fn matches(&self, tissue_box: &TissueBox, index: usize, today: NaiveDate) -> bool {
+		let tissue = &tissue_box.tissues[index];
+		(!self.overdue || tissue.is_overdue(today))
+			&& self.due_within.is_none_or(|span| tissue.is_due_within(today, span))
+			&& (!self.ready || tissue_box.blockers(index).is_empty())
+			&& (!self.too_big || !tissue.oversized(&tissue_box.config.too_big, Utc::now()).is_empty())
+	}
 }
 
 #[derive(Subcommand)]
@@ -57,6 +175,204 @@ pub enum WhichList {
 	Tags,
 }
 
+#[derive(Args)]
+pub struct Show {
+	pub index: usize,
+	/// Index into the recycle bin instead of the open tissues
+	#[arg(short, long)]
+	pub trash: bool,
+}
+
+#[derive(Args)]
+pub struct SetPriority {
+	pub index: usize,
+	/// P0 (most urgent) to P3, or none
+	pub level: Level,
+}
+
+/// A priority, or none at all.
+#[derive(Copy, Clone, Debug)]
+pub struct Level(pub Option<Priority>);
+
+impl std::str::FromStr for Level {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("none") {
+			Ok(Level(None))
+		} else {
+			s.parse().map(|priority| Level(Some(priority)))
+		}
+	}
+}
+
+#[derive(Args)]
+pub struct SetEstimate {
+	pub index: usize,
+	/// A length of time such as 90m, a size from XS to XL, or none
+	pub estimate: Sizing,
+}
+
+/// An estimate, or none at all.
+#[derive(Copy, Clone, Debug)]
+pub struct Sizing(pub Option<Estimate>);
+
+impl std::str::FromStr for Sizing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("none") {
+			Ok(Sizing(None))
+		} else {
+			s.parse().map(|estimate| Sizing(Some(estimate)))
+		}
+	}
+}
+
+#[derive(Args)]
+pub struct SetDue {
+	pub index: usize,
+	/// tomorrow, a weekday such as fri, a span such as 3d, an ISO date such as 2026-11-01, or none
+	pub date: DueDate,
+}
+
+/// A due date, or none at all.
+#[derive(Copy, Clone, Debug)]
+pub struct DueDate(pub Option<NaiveDate>);
+
+impl std::str::FromStr for DueDate {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("none") {
+			Ok(DueDate(None))
+		} else {
+			time::parse_date(s, time::today()).map(|date| DueDate(Some(date)))
+		}
+	}
+}
+
+#[derive(Args)]
+pub struct Publish {
+	#[command(flatten)]
+	pub targets: Targets,
+	/// Where to publish the tissues, overriding the configured backend
+	#[arg(long)]
+	pub to: Option<publish::Backend>,
+	/// Print what would be sent without publishing anything
+	#[arg(long)]
+	pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct Sync {
+	/// Only sync issues on this backend
+	#[arg(long)]
+	pub to: Option<publish::Backend>,
+}
+
+#[derive(Subcommand)]
+pub enum Import {
+	/// Import from GitHub, using the `gh` command
+	Gh(GhImport),
+}
+
+#[derive(Args)]
+pub struct GhImport {
+	/// Issue number, or a search query to import every matching open issue
+	pub query: Option<String>,
+	/// Import every open issue assigned to you
+	#[arg(long)]
+	pub assigned_to_me: bool,
+	/// Import at most this many issues when searching
+	#[arg(long, default_value_t = 100)]
+	pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct Metadata {
+	pub index: usize,
+	/// Replace the assignees. May be repeated
+	#[arg(long = "assignee")]
+	pub assignees: Vec<String>,
+	#[arg(long)]
+	pub milestone: Option<String>,
+	#[arg(long)]
+	pub project: Option<String>,
+	#[arg(long = "type")]
+	pub issue_type: Option<String>,
+}
+
+#[derive(Args)]
+pub struct Commit {
+	pub index: usize,
+	/// Commit even if the tissue has unchecked items and require_checked is set
+	#[arg(short, long)]
+	pub force: bool,
+}
+
+// Targets come before the status, as in `status 3 review`, while `status review --where tag:bug` still works.
+#[derive(Args)]
+#[command(allow_missing_positional = true)]
+pub struct SetStatus {
+	#[command(flatten)]
+	pub targets: Targets,
+	/// One of the statuses in the workflow config, such as "in progress"
+	pub status: String,
+}
+
+#[derive(Args)]
+pub struct Depend {
+	/// The tissue that is blocked
+	pub index: usize,
+	/// The tissue it is blocked by
+	pub on: usize,
+	/// Remove the link instead
+	#[arg(short, long)]
+	pub remove: bool,
+}
+
+#[derive(Args)]
+pub struct Check {
+	pub index: usize,
+	pub item: usize,
+	/// Mark the item as not done instead
+	#[arg(short, long)]
+	pub uncheck: bool,
+}
+
+#[derive(Args)]
+pub struct Start {
+	pub index: usize,
+	/// Only start the timer and star the tissue, without creating a branch
+	#[arg(long)]
+	pub no_branch: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Report {
+	/// Sum the time tracked per tissue and per tag, including closed tissues
+	Time(TimeReport),
+}
+
+#[derive(Args)]
+pub struct TimeReport {
+	/// Only count time within a length of time such as 1w
+	#[arg(long, value_name = "SPAN")]
+	pub since: Option<time::Span>,
+}
+
+#[derive(Args)]
+pub struct Finish {
+	pub index: usize,
+	/// Merge the branch instead of squashing it into a single commit
+	#[arg(long)]
+	pub merge: bool,
+	/// Commit even if the tissue has unchecked items and require_checked is set
+	#[arg(short, long)]
+	pub force: bool,
+}
+
 #[derive(Args)]
 pub struct Add {
 	/// Title of the new issue.
@@ -75,8 +391,55 @@ pub struct Describe {
 #[derive(Args)]
 pub struct Tag {
 	pub tag: String,
-	/// Index of tissue to tag
-	pub index: Option<usize>,
+	/// Which tissues to tag, or the last one if none are given
+	#[command(flatten)]
+	pub targets: Targets,
+}
+
+#[derive(Args)]
+pub struct Tags {
+	#[command(subcommand)]
+	pub action: Option<TagAction>,
+}
+
+#[derive(Subcommand)]
+pub enum TagAction {
+	/// Rename a tag on every tissue
+	Rename(RenameTag),
+	/// Replace several tags with one on every tissue
+	Merge(MergeTags),
+	/// Describe what a tag is for, or clear its description
+	Describe(DescribeTag),
+	/// Set the color a tag is drawn in, or clear it
+	Color(ColorTag),
+}
+
+#[derive(Args)]
+pub struct RenameTag {
+	pub old: String,
+	pub new: String,
+}
+
+#[derive(Args)]
+pub struct MergeTags {
+	#[arg(required = true)]
+	pub tags: Vec<String>,
+	/// The tag to merge them into
+	#[arg(long)]
+	pub into: String,
+}
+
+#[derive(Args)]
+pub struct DescribeTag {
+	pub tag: String,
+	pub description: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ColorTag {
+	pub tag: String,
+	/// A color name such as green, or a hex color such as #d73a4a
+	pub color: Option<String>,
 }
 
 #[derive(Args)]
@@ -88,9 +451,10 @@ pub struct Edit {
 
 #[derive(Args)]
 pub struct Remove {
-	/// Which tissue to delete
-	pub index: usize,
-	/// Remove a single field, instead of the whole tissue
+	/// Which tissues to delete
+	#[command(flatten)]
+	pub targets: Targets,
+	/// Remove a single field, instead of the whole tissues
 	#[command(subcommand)]
 	pub which: Option<WhichRemove>,
 }
@@ -108,51 +472,218 @@ pub struct TagName {
 	pub tag: String,
 }
 
+#[derive(Args)]
+pub struct Ref {
+	pub index: usize,
+	pub reference: Option<Reference>,
+}
+
+#[derive(Args)]
+pub struct Attach {
+	pub index: usize,
+	/// A URL such as https://example.com, or the path of a file to copy
+	pub target: String,
+}
+
+#[derive(Args)]
+pub struct Attachments {
+	pub index: usize,
+	/// Open the attachment with this index using xdg-open
+	#[arg(long, value_name = "ATTACHMENT")]
+	pub open: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct Notes {
+	pub index: usize,
+	/// Print the notes instead of editing them
+	#[arg(short, long)]
+	pub print: bool,
+}
+
+#[derive(Args)]
+pub struct Open {
+	pub index: usize,
+	/// Which of the tissue's references to open
+	#[arg(default_value_t = 0)]
+	pub reference: usize,
+}
+
+#[derive(Args)]
+pub struct Scan {
+	/// Files or directories to scan, defaulting to the whole repository
+	pub paths: Vec<PathBuf>,
+	/// Report comments without tissues and tissues without comments instead of importing
+	#[arg(long)]
+	pub check: bool,
+	/// Comment marker to look for, overriding the configured markers. May be repeated
+	#[arg(short, long = "marker")]
+	pub markers: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Hooks {
+	/// Install prepare-commit-msg and commit-msg hooks, chaining any existing ones
+	Install(HooksInstall),
+	/// Remove installed hooks, restoring any chained ones
+	Uninstall,
+	/// Pre-fill a commit message from the starred tissue
+	#[command(hide = true)]
+	PrepareCommitMsg(PrepareCommitMsg),
+	/// Check that a commit message references an open tissue
+	#[command(hide = true)]
+	CommitMsg(CommitMsg),
+}
+
+#[derive(Args)]
+pub struct HooksInstall {
+	/// Reject commits whose subject doesn't match an open tissue
+	#[arg(long)]
+	pub require_tissue: bool,
+}
+
+#[derive(Args)]
+pub struct PrepareCommitMsg {
+	pub file: PathBuf,
+	pub source: Option<String>,
+	pub commit: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CommitMsg {
+	pub file: PathBuf,
+	#[arg(long)]
+	pub require_tissue: bool,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
 	#[error("no tissue with index {0}")]
 	TissueNotFound(usize),
 	#[error("no description with index {1} on tissue {0}")]
 	DescriptionNotFound(usize, usize),
+	#[error("{0}")]
+	InvalidTag(String),
+	#[error("no tissue is tagged {0}")]
+	UnusedTag(String),
+	#[error("tag {0} is already in use; merge into it instead")]
+	TagExists(String),
+	#[error("invalid color {0:?}")]
+	InvalidColor(String),
 	#[error("no tag named {1} on tissue {0}")]
 	TagNotFound(usize, String),
 	#[error("failed to commit: {0}")]
 	CommitFailed(io::Error),
+	#[error("tissue {0} already waits on tissue {1}, so it can't block it")]
+	DependencyCycle(usize, usize),
+	#[error("tissue {0} doesn't wait on tissue {1}")]
+	DependencyNotFound(usize, usize),
+	#[error("tissue {0} has already been committed")]
+	AlreadyCommitted(usize),
+	#[error("failed to set status: {0}")]
+	StatusFailed(workflow::Error),
+	#[error("tissue {0} has {1} unchecked items; commit with --force to ignore them")]
+	UncheckedItems(usize, usize),
+	#[error("no removed tissue with index {0}")]
+	RemovedTissueNotFound(usize),
+	#[error("failed to show commit: {0}")]
+	ShowFailed(io::Error),
 	#[error("failed to publish: {0}")]
-	PublishFailed(io::Error),
+	PublishFailed(publish::Error),
+	#[error("tissue {0} has already been published as {1}")]
+	AlreadyPublished(usize, String),
+	#[error("failed to import: {0}")]
+	ImportFailed(io::Error),
+	#[error("{0} has already been imported")]
+	AlreadyImported(String),
+	#[error("import command specified without an issue or --assigned-to-me")]
+	InvalidImportCommand,
+	#[error("no timer is running")]
+	NoTimer,
+	#[error("tissue {0} already has a branch")]
+	BranchExists(usize),
+	#[error("tissue {0} has no branch")]
+	NoBranch(usize),
+	#[error("branch operation failed: {0}")]
+	BranchFailed(io::Error),
+	#[error("no reference with index {1} on tissue {0}")]
+	ReferenceNotFound(usize, usize),
+	#[error("failed to read reference: {0}")]
+	ReferenceFailed(io::Error),
+	#[error("failed to open editor: {0}")]
+	EditorFailed(io::Error),
+	#[error("no attachment with index {1} on tissue {0}")]
+	AttachmentNotFound(usize, usize),
+	#[error("failed to attach: {0}")]
+	AttachFailed(io::Error),
+	#[error("failed to open attachment: {0}")]
+	OpenFailed(io::Error),
+	#[error("failed to purge: {0}")]
+	PurgeFailed(io::Error),
+	/// Carries how many tissues were purged before purging failed, which must be saved.
+	#[error("failed to purge: {1}; purged {0} before failing")]
+	PartlyPurged(usize, io::Error),
+	#[error("failed to scan: {0}")]
+	ScanFailed(io::Error),
+	#[error("hook failed: {0}")]
+	HookFailed(io::Error),
+	#[error("commit message does not reference an open tissue")]
+	UnreferencedCommit,
+	#[error("no tissues given; give indices such as 1-4,7 or a query such as --where tag:bug")]
+	NoTargets,
+	#[error("no tissues match the query")]
+	NoMatches,
+	/// Carries the URLs of the issues published before publishing failed, which must be saved.
+	#[error("failed to publish: {1}; published before failing:\n{0}")]
+	PartlyPublished(String, publish::Error),
 	#[error("list command specified without index")]
 	InvalidListCommand,
+	/// Carries the overdue tissues, which are printed rather than logged.
+	#[error("tissues are overdue:\n{0}")]
+	Overdue(String),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 pub fn run(command: Command, tissue_box: &mut TissueBox) -> Result<Option<String>> {
 	match command {
-		Command::List(List { index: None, which: None }) => Ok(Some(tissue_box.to_string())),
-		Command::List(List { index: Some(index), which: None }) => Ok(Some(tissue_box.get(index).map(ToString::to_string).ok_or(Error::TissueNotFound(index))?)),
+		Command::List(List { index: None, which: None, sort, filter }) => {
+			let tissues = match sort {
+				Some(sort) => tissue_box.sorted(sort),
+				None => tissue_box.tissues.iter().enumerate().collect(),
+			};
+			let today = time::today();
+			let indices = tissues.into_iter().map(|(index, _)| index).filter(|&index| filter.matches(tissue_box, index, today)).collect::<Vec<_>>();
+			Ok(Some(tissue_box.entries(indices).map(|entry| entry.to_string()).collect()))
+		}
+		Command::List(List { index: Some(index), which: None, .. }) => Ok(Some(tissue_box.get(index).map(ToString::to_string).ok_or(Error::TissueNotFound(index))?)),
 		Command::List(List {
 			index: Some(index),
 			which: Some(WhichList::Title),
+			..
 		}) => Ok(Some(tissue_box.get(index).map(|x| x.title.clone() + "\n").ok_or(Error::TissueNotFound(index))?)),
 		Command::List(List {
 			index: Some(index),
 			which: Some(WhichList::Description(OptionIndex { index: None })),
-		}) => Ok(Some(tissue_box.get(index).map(|x| x.description.join("\n")).ok_or(Error::TissueNotFound(index))?)),
+			..
+		}) => Ok(Some(tissue_box.get(index).map(|x| x.description.iter().map(|item| item.text.as_str()).collect::<Vec<_>>().join("\n")).ok_or(Error::TissueNotFound(index))?)),
 		Command::List(List {
 			index: Some(tissue_index),
 			which: Some(WhichList::Description(OptionIndex { index: Some(index) })),
+			..
 		}) => Ok(Some(
 			tissue_box
 				.get(tissue_index)
 				.ok_or(Error::TissueNotFound(tissue_index))?
 				.description
 				.get(index)
-				.map(|x| x.clone() + "\n")
+				.map(|x| x.text.clone() + "\n")
 				.ok_or(Error::DescriptionNotFound(tissue_index, index))?,
 		)),
 		Command::List(List {
 			index: Some(index),
 			which: Some(WhichList::Tags),
+			..
 		}) => {
 			let tissue = tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
 			let mut iter = tissue.tags.iter();
@@ -164,7 +695,40 @@ pub fn run(command: Command, tissue_box: &mut TissueBox) -> Result<Option<String
 			tags.push('\n');
 			Ok(Some(tags))
 		}
-		Command::List(List { index: None, which: Some(_) }) => Err(Error::InvalidListCommand),
+		Command::List(List { index: None, which: Some(_), .. }) => Err(Error::InvalidListCommand),
+		Command::Trash => Ok(Some(tissue_box.trash())),
+		Command::Purge(OptionIndex { index }) => {
+			let count = tissue_box.purge(index).map_err(|e| match e {
+				PurgeError::TissueNotFound(index) => Error::RemovedTissueNotFound(index),
+				PurgeError::Io(0, e) => Error::PurgeFailed(e),
+				PurgeError::Io(count, e) => Error::PartlyPurged(count, e),
+			})?;
+			Ok(Some(format!("purged {count} tissue{}\n", if count == 1 { "" } else { "s" })))
+		}
+		Command::Show(Show { index, trash: false }) => {
+			let tissue = tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			let mut out = tissue.to_string();
+			if !tissue.notes.is_empty() {
+				out += &format!("\n{}\n", tissue.notes);
+			}
+			Ok(Some(out))
+		}
+		Command::Show(Show { index, trash: true }) => {
+			let tissue = tissue_box.get_removed(index).ok_or(Error::RemovedTissueNotFound(index))?;
+			let mut out = tissue.to_string();
+			if let Some(resolution) = &tissue.resolution {
+				out += &format!("{resolution}\n");
+			}
+			if let Some(Resolution::Committed { hash }) = &tissue.resolution {
+				let output = std::process::Command::new("git").args(["show", "--stat", hash]).output().map_err(Error::ShowFailed)?;
+				if !output.status.success() {
+					return Err(Error::ShowFailed(io::Error::other(String::from_utf8_lossy(&output.stderr).to_string())));
+				}
+				out += "\n";
+				out += &String::from_utf8_lossy(&output.stdout);
+			}
+			Ok(Some(out))
+		}
 		Command::Add(Add { title }) => {
 			tissue_box.create(title);
 			Ok(None)
@@ -174,9 +738,62 @@ pub fn run(command: Command, tissue_box: &mut TissueBox) -> Result<Option<String
 			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.describe(description);
 			Ok(None)
 		}
-		Command::Tag(Tag { index, tag }) => {
-			let index = index.unwrap_or(tissue_box.tissues.len() - 1);
-			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.tag(tag);
+		Command::Tag(Tag { tag, targets }) => {
+			tissue_box.check_new_tag(&tag, &[]).map_err(Error::InvalidTag)?;
+			for index in targets.select(tissue_box, tissue_box.tissues.len().checked_sub(1))? {
+				tissue_box.tissues[index].tag(tag.clone());
+			}
+			Ok(None)
+		}
+		Command::Tags(Tags { action: None }) => Ok(Some(
+			tissue_box
+				.tag_counts()
+				.into_iter()
+				.map(|(tag, count)| match tissue_box.tag_info(tag).and_then(|info| info.description.as_ref()) {
+					Some(description) => format!("{tag} ({count}): {description}\n"),
+					None => format!("{tag} ({count})\n"),
+				})
+				.collect(),
+		)),
+		Command::Tags(Tags {
+			action: Some(TagAction::Rename(RenameTag { old, new })),
+		}) => {
+			tissue_box.check_new_tag(&new, std::slice::from_ref(&old)).map_err(Error::InvalidTag)?;
+			if tissue_box.has_tag(&new) {
+				return Err(Error::TagExists(new));
+			}
+			if !tissue_box.has_tag(&old) {
+				return Err(Error::UnusedTag(old));
+			}
+			let count = tissue_box.merge_tags(std::slice::from_ref(&old), &new);
+			Ok(Some(format!("renamed {old} to {new} on {count} tissue{}\n", if count == 1 { "" } else { "s" })))
+		}
+		Command::Tags(Tags {
+			action: Some(TagAction::Merge(MergeTags { tags, into })),
+		}) => {
+			tissue_box.check_new_tag(&into, &tags).map_err(Error::InvalidTag)?;
+			match tissue_box.merge_tags(&tags, &into) {
+				0 => Err(Error::UnusedTag(tags.join(", "))),
+				count => Ok(Some(format!("merged into {into} on {count} tissue{}\n", if count == 1 { "" } else { "s" }))),
+			}
+		}
+		Command::Tags(Tags {
+			action: Some(TagAction::Describe(DescribeTag { tag, description })),
+		}) => {
+			tissue_box.check_new_tag(&tag, &[]).map_err(Error::InvalidTag)?;
+			let info = tissue_box.tag_info(&tag).cloned().unwrap_or_default();
+			tissue_box.set_tag_info(&tag, TagInfo { description, ..info });
+			Ok(None)
+		}
+		Command::Tags(Tags {
+			action: Some(TagAction::Color(ColorTag { tag, color })),
+		}) => {
+			tissue_box.check_new_tag(&tag, &[]).map_err(Error::InvalidTag)?;
+			if let Some(color) = &color {
+				color.parse::<ratatui::style::Color>().map_err(|_| Error::InvalidColor(color.clone()))?;
+			}
+			let info = tissue_box.tag_info(&tag).cloned().unwrap_or_default();
+			tissue_box.set_tag_info(&tag, TagInfo { color, ..info });
 			Ok(None)
 		}
 		Command::Edit(Edit { title, index }) => {
@@ -184,38 +801,392 @@ pub fn run(command: Command, tissue_box: &mut TissueBox) -> Result<Option<String
 			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.title = title;
 			Ok(None)
 		}
-		Command::Remove(Remove { index, which: None }) => {
-			tissue_box.remove(index).ok_or(Error::TissueNotFound(index))?;
+		Command::Remove(Remove { targets, which: None }) => {
+			let indices = targets.select(tissue_box, None)?;
+			tissue_box.remove_all(&indices);
 			Ok(None)
 		}
 		Command::Remove(Remove {
-			index: tissue_index,
+			targets,
 			which: Some(WhichRemove::Description(Index { index })),
 		}) => {
-			let tissue = tissue_box.get_mut(tissue_index).ok_or(Error::TissueNotFound(tissue_index))?;
-			tissue.description.get(index).ok_or(Error::DescriptionNotFound(tissue_index, index))?;
-			tissue.description.remove(index);
+			let tissue_indices = targets.select(tissue_box, None)?;
+			if let Some(&tissue_index) = tissue_indices.iter().find(|&&tissue_index| tissue_box.tissues[tissue_index].description.get(index).is_none()) {
+				return Err(Error::DescriptionNotFound(tissue_index, index));
+			}
+			for tissue_index in tissue_indices {
+				tissue_box.tissues[tissue_index].description.remove(index);
+			}
 			Ok(None)
 		}
 		Command::Remove(Remove {
-			index,
+			targets,
 			which: Some(WhichRemove::Tag(TagName { tag })),
 		}) => {
-			if tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.tags.remove(&tag) {
+			let indices = targets.select(tissue_box, None)?;
+			if let Some(&index) = indices.iter().find(|&&index| !tissue_box.tissues[index].tags.contains(&tag)) {
+				return Err(Error::TagNotFound(index, tag));
+			}
+			for index in indices {
+				tissue_box.tissues[index].tags.remove(&tag);
+			}
+			Ok(None)
+		}
+		Command::Commit(Commit { index, force }) => {
+			let tissue = tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			if tissue.resolution.is_some() {
+				return Err(Error::AlreadyCommitted(index));
+			}
+			let hash = tissue.commit(tissue_box.config(), force).map_err(|e| match e {
+				CommitError::UncheckedItems(count) => Error::UncheckedItems(index, count),
+				CommitError::Git(e) => Error::CommitFailed(e),
+			})?;
+			warn_if_blocked(tissue_box, index);
+			tissue_box.close_committed(index, hash).expect("index used by get");
+			Ok(None)
+		}
+		Command::Depend(Depend { index, on, remove }) => {
+			let blocker = tissue_box.get(on).ok_or(Error::TissueNotFound(on))?.id;
+			tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			if remove {
+				let blocked_by = &mut tissue_box.tissues[index].blocked_by;
+				let count = blocked_by.len();
+				blocked_by.retain(|&id| id != blocker);
+				if blocked_by.len() == count {
+					return Err(Error::DependencyNotFound(index, on));
+				}
+			} else if index == on || tissue_box.depends_on(on, index) {
+				return Err(Error::DependencyCycle(on, index));
+			} else if !tissue_box.tissues[index].blocked_by.contains(&blocker) {
+				tissue_box.tissues[index].blocked_by.push(blocker);
+			}
+			Ok(None)
+		}
+		Command::Split(Index { index }) => {
+			tissue_box.split(index).ok_or(Error::TissueNotFound(index))?;
+			Ok(None)
+		}
+		Command::Check(Check { index: tissue_index, item: index, uncheck }) => {
+			let tissue = tissue_box.get_mut(tissue_index).ok_or(Error::TissueNotFound(tissue_index))?;
+			tissue.description.get_mut(index).ok_or(Error::DescriptionNotFound(tissue_index, index))?.done = !uncheck;
+			Ok(None)
+		}
+		Command::Status(SetStatus { status, targets }) => {
+			let indices = targets.select(tissue_box, None)?;
+			for &index in &indices {
+				tissue_box.config.workflow.check(tissue_box.status(index), &status).map_err(Error::StatusFailed)?;
+			}
+			for index in indices {
+				tissue_box.set_status(index, &status).expect("checked above");
+			}
+			Ok(None)
+		}
+		Command::Priority(SetPriority { index, level: Level(priority) }) => {
+			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.priority = priority;
+			Ok(None)
+		}
+		Command::Estimate(SetEstimate { index, estimate: Sizing(estimate) }) => {
+			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.estimate = estimate;
+			Ok(None)
+		}
+		Command::Due(SetDue { index, date: DueDate(due) }) => {
+			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.due = due;
+			Ok(None)
+		}
+		Command::Remind => {
+			let today = time::today();
+			let overdue = tissue_box
+				.entries((0..tissue_box.tissues.len()).filter(|&index| tissue_box.tissues[index].is_overdue(today)))
+				.map(|entry| entry.to_string())
+				.collect::<String>();
+			if overdue.is_empty() {
 				Ok(None)
 			} else {
-				Err(Error::TagNotFound(index, tag))
+				Err(Error::Overdue(overdue))
+			}
+		}
+		Command::Publish(Publish { targets, to, dry_run: true }) => {
+			let plans = targets
+				.select(tissue_box, None)?
+				.into_iter()
+				.map(|index| tissue_box.config().publish.plan(&tissue_box.tissues[index], to).map(|plan| plan.to_string()))
+				.collect::<publish::Result<Vec<_>>>()
+				.map_err(Error::PublishFailed)?;
+			Ok(Some(plans.join("\n")))
+		}
+		Command::Publish(Publish { targets, to, dry_run: false }) => {
+			let indices = targets.select(tissue_box, None)?;
+			if let Some((index, issue)) = indices.iter().find_map(|&index| Some((index, tissue_box.tissues[index].issue.as_ref()?))) {
+				return Err(Error::AlreadyPublished(index, issue.url.clone()));
+			}
+			let plans = indices
+				.into_iter()
+				.map(|index| Ok((index, tissue_box.config().publish.plan(&tissue_box.tissues[index], to)?)))
+				.collect::<publish::Result<Vec<_>>>()
+				.map_err(Error::PublishFailed)?;
+			let (issues, result) = tissue_box.publish_all(&plans, to);
+			let out = issues.iter().map(|issue| format!("{}\n", issue.url)).collect::<String>();
+			match result {
+				Ok(()) => Ok(Some(out)),
+				Err(e) if issues.is_empty() => Err(Error::PublishFailed(e)),
+				Err(e) => Err(Error::PartlyPublished(out, e)),
+			}
+		}
+		Command::Sync(Sync { to }) => {
+			let config = &tissue_box.config.publish;
+			let mut out = String::new();
+			let mut closed = Vec::new();
+			for (index, tissue) in tissue_box.tissues.iter_mut().enumerate() {
+				let Some((url, backend)) = tissue.issue.as_ref().map(|issue| (issue.url.clone(), issue.backend)) else {
+					continue;
+				};
+				if to.is_some_and(|to| to != backend) {
+					continue;
+				}
+				let result = config.publisher(Some(backend)).map_err(io::Error::other).and_then(|publisher| sync::sync(tissue, &*publisher, config.body_style));
+				match result {
+					Ok(changes) => {
+						if changes.contains(&sync::Change::Closed) {
+							closed.push(index);
+						}
+						for change in changes {
+							out += &format!("{url}: {change}\n");
+						}
+					}
+					// Keep going, so one unreachable issue doesn't hold up the rest.
+					Err(e) => out += &format!("{url}: failed to sync: {e}\n"),
+				}
+			}
+			for index in closed.into_iter().rev() {
+				let issue = tissue_box.get(index).and_then(|tissue| tissue.issue.clone()).expect("closed tissues are linked");
+				tissue_box.close(index, issue.into()).expect("index from enumerate");
+			}
+			Ok(Some(out))
+		}
+		Command::Import(Import::Gh(GhImport { query, assigned_to_me, limit })) => {
+			let github = &tissue_box.config().publish.github;
+			let issues = match query.as_deref().map(str::parse::<u64>) {
+				Some(Ok(number)) => {
+					let issue = github.fetch(number).map_err(Error::ImportFailed)?;
+					if tissue_box.find_issue(&issue.issue.url).is_some() {
+						return Err(Error::AlreadyImported(issue.issue.url));
+					}
+					vec![issue]
+				}
+				None if !assigned_to_me => return Err(Error::InvalidImportCommand),
+				_ => {
+					let issues = github.search(query.as_deref(), assigned_to_me, limit).map_err(Error::ImportFailed)?;
+					if issues.len() == limit {
+						tracing::warn!("only the first {limit} matching issues were imported; pass a larger --limit to import more");
+					}
+					issues
+				}
+			};
+			let mut out = String::new();
+			for issue in issues {
+				if tissue_box.find_issue(&issue.issue.url).is_some() {
+					out += &format!("skipped: {} (already imported)\n", issue.issue.url);
+				} else {
+					out += &format!("imported: {} {}\n", issue.issue.url, issue.title);
+					tissue_box.add(issue.to_tissue());
+				}
+			}
+			Ok(Some(out))
+		}
+		Command::Metadata(Metadata {
+			index,
+			assignees,
+			milestone,
+			project,
+			issue_type,
+		}) => {
+			let metadata = &mut tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.metadata;
+			if assignees.is_empty() && milestone.is_none() && project.is_none() && issue_type.is_none() {
+				return Ok(Some(metadata.to_string()));
+			}
+			if !assignees.is_empty() {
+				metadata.assignees = assignees;
+			}
+			metadata.milestone = milestone.or(metadata.milestone.take());
+			metadata.project = project.or(metadata.project.take());
+			metadata.issue_type = issue_type.or(metadata.issue_type.take());
+			Ok(None)
+		}
+		Command::Start(Start { index, no_branch }) => {
+			let tissue = tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?;
+			if !no_branch {
+				if tissue.branch.is_some() {
+					return Err(Error::BranchExists(index));
+				}
+				tissue.start_branch().map_err(Error::BranchFailed)?;
+			}
+			let stopped = tissue_box.start_timer(index).expect("index used by get_mut");
+			tissue_box.star(Some(index));
+			Ok(stopped.filter(|&stopped| stopped != index).map(|stopped| format!("stopped timing tissue {stopped}\n")))
+		}
+		Command::Stop => {
+			let index = tissue_box.stop_timer().ok_or(Error::NoTimer)?;
+			let tracked = tissue_box.tissues[index].tracked(None, Utc::now());
+			Ok(Some(format!("{index}. {} ({} in total)\n", tissue_box.tissues[index].title, time::Span(tracked))))
+		}
+		Command::Report(Report::Time(TimeReport { since })) => Ok(Some(report_time(tissue_box, since))),
+		Command::Finish(Finish { index, merge, force }) => {
+			let tissue = tissue_box.tissues.get_mut(index).ok_or(Error::TissueNotFound(index))?;
+			if tissue.branch.is_none() {
+				return Err(Error::NoBranch(index));
+			}
+			let hash = tissue.finish_branch(merge, &tissue_box.config, force).map_err(|e| match e {
+				CommitError::UncheckedItems(count) => Error::UncheckedItems(index, count),
+				CommitError::Git(e) => Error::BranchFailed(e),
+			})?;
+			warn_if_blocked(tissue_box, index);
+			tissue_box.close_committed(index, hash).expect("index used by get_mut");
+			Ok(None)
+		}
+		Command::Ref(Ref { index, reference: Some(mut reference) }) => {
+			let tissue = tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?;
+			reference.capture().map_err(Error::ReferenceFailed)?;
+			tissue.references.push(reference);
+			Ok(None)
+		}
+		Command::Ref(Ref { index, reference: None }) => {
+			let tissue = tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?;
+			let mut out = String::new();
+			for (i, reference) in tissue.references.iter_mut().enumerate() {
+				// A reference to a file that has since been deleted is still worth listing.
+				let _ = reference.reanchor();
+				out += &format!("{i}. {reference}\n");
+			}
+			Ok(Some(out))
+		}
+		Command::Open(Open { index, reference }) => {
+			let tissue = tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?;
+			let reference = tissue.references.get_mut(reference).ok_or(Error::ReferenceNotFound(index, reference))?;
+			reference.reanchor().map_err(Error::ReferenceFailed)?;
+			reference.open().map_err(Error::EditorFailed)?;
+			Ok(None)
+		}
+		Command::Notes(Notes { index, print: true }) => {
+			let notes = &tissue_box.get(index).ok_or(Error::TissueNotFound(index))?.notes;
+			Ok((!notes.is_empty()).then(|| format!("{notes}\n")))
+		}
+		Command::Notes(Notes { index, print: false }) => {
+			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.edit_notes().map_err(Error::EditorFailed)?;
+			Ok(None)
+		}
+		Command::Attach(Attach { index, target }) => {
+			tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			tissue_box.attach(index, &target).map_err(Error::AttachFailed)?;
+			Ok(None)
+		}
+		Command::Attachments(Attachments { index, open: None }) => {
+			let tissue = tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			Ok(Some(
+				tissue
+					.attachments
+					.iter()
+					.enumerate()
+					.map(|(i, attachment)| match attachment {
+						Attachment::Link { url } => format!("{i}. link {url}\n"),
+						Attachment::File { name } => format!("{i}. file {name}\n"),
+					})
+					.collect(),
+			))
+		}
+		Command::Attachments(Attachments { index, open: Some(attachment) }) => {
+			let tissue = tissue_box.get(index).ok_or(Error::TissueNotFound(index))?;
+			let target = tissue
+				.attachments
+				.get(attachment)
+				.and_then(|attachment| tissue_box.attachment_target(tissue, attachment))
+				.ok_or(Error::AttachmentNotFound(index, attachment))?;
+			open_externally(&target).map_err(Error::OpenFailed)?;
+			Ok(None)
+		}
+		Command::Scan(Scan { paths, check, markers }) => {
+			let markers = if markers.is_empty() { tissue_box.config().markers.clone() } else { markers };
+			let files = scan::files(&paths).map_err(Error::ScanFailed)?;
+			let comments = scan::comments(&files, &markers).map_err(Error::ScanFailed)?;
+			let mut out = String::new();
+			if check {
+				let check = scan::check(tissue_box, &paths, &comments, &markers);
+				for comment in &check.untracked {
+					out += &format!("untracked: {comment}\n");
+				}
+				for index in check.stale {
+					let tissue = tissue_box.get(index).expect("index returned by check");
+					for reference in &tissue.references {
+						out += &format!("stale: {reference}: {index}. {}\n", tissue.title);
+					}
+				}
+			} else {
+				for comment in scan::import(tissue_box, &comments) {
+					out += &format!("added: {comment}\n");
+				}
 			}
+			Ok(Some(out))
 		}
-		Command::Commit(Index { index }) => {
-			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.commit().map_err(Error::CommitFailed)?;
-			tissue_box.remove(index).expect("index used by get_mut");
+		Command::Hooks(Hooks::Install(HooksInstall { require_tissue })) => {
+			let program = std::env::current_exe().unwrap_or_else(|_| "tissue".into());
+			hooks::install(&hooks::hooks_dir().map_err(Error::HookFailed)?, &program, require_tissue).map_err(Error::HookFailed)?;
 			Ok(None)
 		}
-		Command::Publish(Index { index }) => {
-			tissue_box.get_mut(index).ok_or(Error::TissueNotFound(index))?.publish().map_err(Error::PublishFailed)?;
-			tissue_box.remove(index).expect("index used by get_mut");
+		Command::Hooks(Hooks::Uninstall) => {
+			hooks::uninstall(&hooks::hooks_dir().map_err(Error::HookFailed)?).map_err(Error::HookFailed)?;
 			Ok(None)
 		}
+		Command::Hooks(Hooks::PrepareCommitMsg(PrepareCommitMsg { file, source, commit: _ })) => {
+			hooks::prepare_commit_msg(tissue_box, &file, source.as_deref()).map_err(Error::HookFailed)?;
+			Ok(None)
+		}
+		Command::Hooks(Hooks::CommitMsg(CommitMsg { file, require_tissue })) => {
+			if hooks::references_tissue(tissue_box, &file).map_err(Error::HookFailed)? {
+				Ok(None)
+			} else if require_tissue {
+				Err(Error::UnreferencedCommit)
+			} else {
+				Ok(Some("note: commit message does not reference an open tissue\n".into()))
+			}
+		}
+	}
+}
+
+/// Committing a blocked tissue is allowed, since its blockers may have been done in the same commit.
+fn warn_if_blocked(tissue_box: &TissueBox, index: usize) {
+	let blockers = tissue_box.blockers(index);
+	if !blockers.is_empty() {
+		tracing::warn!("tissue {index} was still blocked by {}", blockers.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "));
+	}
+}
+
+/// Sum the time tracked on open and closed tissues since `since` ago, per tissue and per tag, longest first.
+fn report_time(tissue_box: &TissueBox, since: Option<time::Span>) -> String {
+	let now = Utc::now();
+	// Spans reaching back past the first representable time cover everything.
+	let since = since.and_then(|span| now.checked_sub_signed(span.0));
+	let mut tissues = Vec::new();
+	let mut tags = HashMap::<&str, TimeDelta>::new();
+	for tissue in tissue_box.tissues.iter().chain(&tissue_box.recycle_bin) {
+		let tracked = tissue.tracked(since, now);
+		if tracked > TimeDelta::zero() {
+			tissues.push((tracked, tissue.title.as_str()));
+			for tag in &tissue.tags {
+				*tags.entry(tag).or_default() += tracked;
+			}
+		}
+	}
+	let total = tissues.iter().map(|(tracked, _)| *tracked).sum();
+	let mut tags = tags.into_iter().map(|(tag, tracked)| (tracked, tag)).collect::<Vec<_>>();
+	let mut out = String::new();
+	for (heading, rows) in [("Tissues", &mut tissues), ("Tags", &mut tags)] {
+		if rows.is_empty() {
+			continue;
+		}
+		rows.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+		out += &format!("{heading}:\n");
+		for (tracked, name) in rows.iter() {
+			out += &format!("  {:>8}  {name}\n", time::Span(*tracked).to_string());
+		}
 	}
+	out + &format!("Total: {}\n", time::Span(total))
 }
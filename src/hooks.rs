@@ -0,0 +1,143 @@
+//! Git hook integration.
+//!
+//! Installed hooks call back into the tissue binary,
+//! so commits written in an editor can still be driven by the tissue box.
+//! Any hook that already exists is moved aside and chained before ours.
+
+use crate::{prelude::*, shell_quote};
+use std::{
+	path::{Path, PathBuf},
+	process,
+};
+
+/// Marks hook scripts written by tissuebox so they are never mistaken for user hooks.
+const MARKER: &str = "# Installed by tissuebox";
+/// Suffix given to a pre-existing hook when it is moved aside.
+const CHAINED_SUFFIX: &str = "tissuebox-chained";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Hook {
+	PrepareCommitMsg,
+	CommitMsg,
+}
+
+impl Hook {
+	pub const ALL: [Hook; 2] = [Hook::PrepareCommitMsg, Hook::CommitMsg];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Hook::PrepareCommitMsg => "prepare-commit-msg",
+			Hook::CommitMsg => "commit-msg",
+		}
+	}
+
+	fn script(self, program: &Path, require_tissue: bool) -> String {
+		let program = shell_quote(&program.to_string_lossy());
+		let chained = format!("\"$0.{CHAINED_SUFFIX}\"");
+		let invocation = match (self, require_tissue) {
+			// Never block a commit because the tissue box couldn't be read.
+			(Hook::PrepareCommitMsg, _) => format!("{program} hooks prepare-commit-msg \"$@\" || true"),
+			(Hook::CommitMsg, false) => format!("{program} hooks commit-msg \"$1\" || true"),
+			(Hook::CommitMsg, true) => format!("exec {program} hooks commit-msg --require-tissue \"$1\""),
+		};
+		format!("#!/bin/sh\n{MARKER}\nif [ -x {chained} ]; then\n\t{chained} \"$@\" || exit $?\nfi\n{invocation}\n")
+	}
+}
+
+fn is_ours(path: &Path) -> io::Result<bool> {
+	match fs::read_to_string(path) {
+		Ok(contents) => Ok(contents.lines().nth(1) == Some(MARKER)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+		// Binary hooks are never ours.
+		Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(false),
+		Err(e) => Err(e),
+	}
+}
+
+fn chained_path(path: &Path) -> PathBuf {
+	let mut chained = path.as_os_str().to_owned();
+	chained.push(".");
+	chained.push(CHAINED_SUFFIX);
+	chained.into()
+}
+
+/// Locate the hooks directory of the current repository, respecting `core.hooksPath`.
+pub fn hooks_dir() -> io::Result<PathBuf> {
+	let output = process::Command::new("git").args(["rev-parse", "--git-path", "hooks"]).output()?;
+	if output.status.success() {
+		Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim_end()))
+	} else {
+		Err(io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()))
+	}
+}
+
+/// Install every hook into `dir`, running `program` when they fire.
+///
+/// Reinstalling replaces previously installed tissuebox hooks without touching chained ones.
+pub fn install(dir: &Path, program: &Path, require_tissue: bool) -> io::Result<()> {
+	fs::create_dir_all(dir)?;
+	for hook in Hook::ALL {
+		let path = dir.join(hook.name());
+		if path.try_exists()? && !is_ours(&path)? {
+			let chained = chained_path(&path);
+			if chained.try_exists()? {
+				return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", chained.display())));
+			}
+			fs::rename(&path, chained)?;
+		}
+		fs::write(&path, hook.script(program, require_tissue))?;
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+		}
+	}
+	Ok(())
+}
+
+/// Remove every hook installed by [`install`] from `dir`, putting chained user hooks back in place.
+pub fn uninstall(dir: &Path) -> io::Result<()> {
+	for hook in Hook::ALL {
+		let path = dir.join(hook.name());
+		if !is_ours(&path)? {
+			continue;
+		}
+		fs::remove_file(&path)?;
+		let chained = chained_path(&path);
+		if chained.try_exists()? {
+			fs::rename(chained, &path)?;
+		}
+	}
+	Ok(())
+}
+
+/// Pre-fill the commit message at `file` from the starred tissue.
+///
+/// `source` is the second argument git passes to `prepare-commit-msg`.
+/// Messages that already have a source (`-m`, merges, amends, ...) are left untouched.
+pub fn prepare_commit_msg(tissue_box: &TissueBox, file: &Path, source: Option<&str>) -> io::Result<()> {
+	if source.is_some() {
+		return Ok(());
+	}
+	let Some(tissue) = tissue_box.starred() else {
+		return Ok(());
+	};
+	let template = fs::read_to_string(file)?;
+	fs::write(file, tissue.commit_message(tissue_box.config()) + "\n" + &template)
+}
+
+/// Check whether the commit message at `file` references an open tissue.
+///
+/// A message references a tissue when its subject line matches the tissue's commit message subject.
+pub fn references_tissue(tissue_box: &TissueBox, file: &Path) -> io::Result<bool> {
+	let message = fs::read_to_string(file)?;
+	let Some(subject) = message.lines().find(|line| !line.starts_with('#') && !line.trim().is_empty()) else {
+		return Ok(false);
+	};
+	// Committed tissues kept for history aren't open.
+	Ok(tissue_box
+		.tissues
+		.iter()
+		.filter(|tissue| tissue.resolution.is_none())
+		.any(|tissue| tissue.commit_message(tissue_box.config()).lines().next() == Some(subject.trim_end())))
+}
@@ -1,94 +1,861 @@
 pub mod cli;
+pub mod hooks;
+pub mod publish;
+pub mod scan;
+pub mod select;
+pub mod sync;
+pub mod time;
 pub mod tui;
+pub mod workflow;
 
 pub mod prelude {
 	pub use super::*;
 	pub use cli::Cli;
 }
 
-use std::{collections::HashSet, fs, io, path::Path};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	ffi::{OsStr, OsString},
+	fs, io,
+	path::{Path, PathBuf},
+};
 
 pub const DAEMONIZE_ARG: &str = "__internal_daemonize";
 
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Tissue {
+	/// Identifies the tissue for as long as it exists, unlike its index.
+	#[serde(default)]
+	pub id: u64,
+	/// The tissue this is a subtask of.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub parent: Option<u64>,
+	/// IDs of the tissues that have to be done before this one.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub blocked_by: Vec<u64>,
 	pub title: String,
 	#[serde(default)]
-	pub description: Vec<String>,
+	pub description: Vec<Item>,
+	/// Free-form markdown, for anything too long for the description.
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub notes: String,
+	/// Kept sorted, even when read from a box saved with tags in another order.
 	#[serde(default)]
-	pub tags: HashSet<String>,
+	pub tags: BTreeSet<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub priority: Option<Priority>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub estimate: Option<Estimate>,
+	/// Unset for tissues still in the workflow's first status.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub status: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub due: Option<NaiveDate>,
+	/// Unset for tissues created before creation times were recorded.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub created: Option<DateTime<Utc>>,
+	/// When this tissue last changed, as of the last time the tissue box was saved.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub updated: Option<DateTime<Utc>>,
+	/// Stretches of time spent working on this tissue, the last of which may still be running.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub work: Vec<Interval>,
+	/// How this tissue left the box, if it is in the recycle bin.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub resolution: Option<Resolution>,
+	/// The git branch this tissue is being worked on, if any.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub branch: Option<Branch>,
+	/// The tracker issue this tissue was published to or imported from.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub issue: Option<publish::Issue>,
+	/// The state of this tissue and its issue at the last sync.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub synced: Option<sync::Synced>,
+	/// Assignees, milestone and so on for when this tissue is published.
+	#[serde(default, skip_serializing_if = "publish::Metadata::is_empty")]
+	pub metadata: publish::Metadata,
+	/// Places in the source code this tissue concerns.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub references: Vec<Reference>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub attachments: Vec<Attachment>,
+}
+
+/// A stretch of time spent working on a tissue.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Interval {
+	pub start: DateTime<Utc>,
+	/// Unset while the timer is running.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub end: Option<DateTime<Utc>>,
+}
+
+impl Interval {
+	/// How much of this interval falls after `since`, counting a running interval up to `now`.
+	pub fn duration(&self, since: Option<DateTime<Utc>>, now: DateTime<Utc>) -> TimeDelta {
+		let start = since.map_or(self.start, |since| self.start.max(since));
+		(self.end.unwrap_or(now) - start).max(TimeDelta::zero())
+	}
+}
+
+/// A line of a tissue's description, which can be checked off as a step.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "ItemRepr", into = "ItemRepr")]
+pub struct Item {
+	pub text: String,
+	pub done: bool,
+}
+
+/// Unchecked items are stored as plain strings, as every description was before items could be checked.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum ItemRepr {
+	Text(String),
+	Item {
+		text: String,
+		#[serde(default)]
+		done: bool,
+	},
+}
+
+impl From<ItemRepr> for Item {
+	fn from(repr: ItemRepr) -> Self {
+		match repr {
+			ItemRepr::Text(text) => Item { text, done: false },
+			ItemRepr::Item { text, done } => Item { text, done },
+		}
+	}
+}
+
+impl From<Item> for ItemRepr {
+	fn from(Item { text, done }: Item) -> Self {
+		if done {
+			ItemRepr::Item { text, done }
+		} else {
+			ItemRepr::Text(text)
+		}
+	}
+}
+
+impl From<String> for Item {
+	fn from(text: String) -> Self {
+		Item { text, done: false }
+	}
+}
+
+impl std::fmt::Display for Item {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "[{}] {}", if self.done { 'x' } else { ' ' }, self.text)
+	}
+}
+
+/// What a tag is for, shared by every tissue with it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TagInfo {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// A color name such as `green`, or a hex color such as `#d73a4a`, used to draw the tag in the TUI.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub color: Option<String>,
+}
+
+impl TagInfo {
+	fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+}
+
+/// The longest tag GitHub accepts as a label name.
+const MAX_TAG_LENGTH: usize = 50;
+
+/// Check that `tag` can be published as a label and come back unchanged.
+pub fn check_tag(tag: &str) -> Result<(), String> {
+	if tag.trim().is_empty() {
+		Err("tags can't be blank".into())
+	} else if tag.trim() != tag {
+		Err(format!("tag {tag:?} can't start or end with whitespace"))
+	} else if tag.chars().count() > MAX_TAG_LENGTH {
+		Err(format!("tag {tag:?} is longer than {MAX_TAG_LENGTH} characters"))
+	} else if tag.contains(',') {
+		// gh reads commas as separating labels.
+		Err(format!("tag {tag:?} can't contain a comma"))
+	} else if tag.chars().any(char::is_control) {
+		Err(format!("tag {tag:?} can't contain control characters"))
+	} else {
+		Ok(())
+	}
+}
+
+/// A link or small file kept with a tissue, such as a pull request or a log.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Attachment {
+	Link {
+		url: String,
+	},
+	/// A copy of a file, kept in the tissue's attachment directory.
+	File {
+		name: String,
+	},
+}
+
+impl Attachment {
+	/// Whether `target` is attached as a link rather than as a copy of a file.
+	fn is_link(target: &str) -> bool {
+		target.contains("://") || target.starts_with("mailto:")
+	}
+}
+
+impl std::fmt::Display for Attachment {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Attachment::Link { url } => write!(f, "{url}"),
+			Attachment::File { name } => write!(f, "{name}"),
+		}
+	}
+}
+
+/// Open a link or file in the desktop's default application for it.
+pub fn open_externally(target: &OsStr) -> io::Result<()> {
+	let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+	let output = std::process::Command::new(program).arg(target).stdin(std::process::Stdio::null()).output()?;
+	if output.status.success() {
+		Ok(())
+	} else {
+		Err(io::Error::other(format!("{program} failed: {}", String::from_utf8_lossy(&output.stderr).trim())))
+	}
+}
+
+/// A `path:line[:column]` location in the source code.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Reference {
+	pub path: PathBuf,
+	pub line: usize,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub column: Option<usize>,
+	/// The text around the referenced line, used to follow it when lines move.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub context: Option<Context>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Context {
+	pub before: String,
+	pub line: String,
+	pub after: String,
+}
+
+impl Context {
+	/// Capture the context of the 1-indexed `line` in `lines`.
+	pub(crate) fn capture(lines: &[&str], line: usize) -> Option<Self> {
+		let index = line.checked_sub(1)?;
+		let get = |i: Option<usize>| i.and_then(|i| lines.get(i)).map(|l| l.trim().to_string()).unwrap_or_default();
+		Some(Self {
+			before: get(index.checked_sub(1)),
+			line: lines.get(index)?.trim().to_string(),
+			after: get(Some(index + 1)),
+		})
+	}
+}
+
+impl Reference {
+	/// Record the text around this reference so it can be re-anchored later.
+	pub fn capture(&mut self) -> io::Result<()> {
+		let contents = fs::read_to_string(&self.path)?;
+		self.context = Context::capture(&contents.lines().collect::<Vec<_>>(), self.line);
+		Ok(())
+	}
+
+	/// Move this reference to wherever its line went, using the captured context.
+	///
+	/// Among lines matching the referenced text, the one with the most matching neighbours wins,
+	/// and ties go to the line closest to the old position.
+	/// Returns whether the reference moved.
+	pub fn reanchor(&mut self) -> io::Result<bool> {
+		let Some(context) = &self.context else {
+			return Ok(false);
+		};
+		let contents = fs::read_to_string(&self.path)?;
+		let lines = contents.lines().collect::<Vec<_>>();
+		// Matching the line alone isn't enough, since lines such as `}` repeat.
+		if Context::capture(&lines, self.line).is_some_and(|current| current == *context) {
+			return Ok(false);
+		}
+		let best = lines
+			.iter()
+			.enumerate()
+			.filter(|(_, line)| line.trim() == context.line)
+			.map(|(index, _)| {
+				let candidate = Context::capture(&lines, index + 1).expect("index is in bounds");
+				let score = (candidate.before == context.before) as usize + (candidate.after == context.after) as usize;
+				(index + 1, score)
+			})
+			.max_by_key(|(line, score)| (*score, std::cmp::Reverse(line.abs_diff(self.line))));
+		let Some((line, _)) = best else {
+			return Ok(false);
+		};
+		let moved = line != self.line;
+		self.line = line;
+		self.context = Context::capture(&lines, line);
+		Ok(moved)
+	}
+
+	/// Open this reference in the user's editor.
+	pub fn open(&self) -> io::Result<()> {
+		let mut command = editor();
+		let program = Path::new(command.get_program()).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+		let column = self.column.unwrap_or(1);
+		match program.as_str() {
+			"code" | "codium" => command.arg("--goto").arg(format!("{}:{}:{column}", self.path.display(), self.line)),
+			"hx" | "helix" => command.arg(format!("{}:{}:{column}", self.path.display(), self.line)),
+			_ => command.arg(format!("+{}", self.line)).arg(&self.path),
+		};
+		let status = command.status()?;
+		if status.success() {
+			Ok(())
+		} else {
+			Err(io::Error::other(format!("editor exited with {status}")))
+		}
+	}
+}
+
+/// The user's editor, from `$VISUAL` or `$EDITOR`, falling back to `vi`.
+pub fn editor() -> std::process::Command {
+	let editor = std::env::var("VISUAL").or_else(|_| std::env::var("EDITOR")).unwrap_or_else(|_| "vi".into());
+	let mut words = editor.split_whitespace();
+	let mut command = std::process::Command::new(words.next().unwrap_or("vi"));
+	command.args(words);
+	command
+}
+
+impl std::str::FromStr for Reference {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("expected path:line[:column], got \"{s}\"");
+		let (rest, last) = s.rsplit_once(':').ok_or_else(invalid)?;
+		let last = last.parse().map_err(|_| invalid())?;
+		let (path, line, column) = match rest.rsplit_once(':').map(|(path, line)| (path, line.parse())) {
+			Some((path, Ok(line))) => (path, line, Some(last)),
+			_ => (rest, last, None),
+		};
+		if path.is_empty() || line == 0 {
+			return Err(invalid());
+		}
+		Ok(Self { path: path.into(), line, column, context: None })
+	}
+}
+
+impl std::fmt::Display for Reference {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}", self.path.display(), self.line)?;
+		if let Some(column) = self.column {
+			write!(f, ":{column}")?;
+		}
+		Ok(())
+	}
+}
+
+/// How urgent a tissue is, from `P0` (drop everything) to `P3`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum Priority {
+	P0,
+	P1,
+	P2,
+	P3,
+}
+
+impl Priority {
+	const ALL: [Priority; 4] = [Priority::P0, Priority::P1, Priority::P2, Priority::P3];
+
+	/// One level more urgent, starting from `P3` for tissues without a priority.
+	pub fn raise(priority: Option<Self>) -> Option<Self> {
+		match priority {
+			None => Some(Priority::P3),
+			Some(priority) => Some(Self::ALL[(priority as usize).saturating_sub(1)]),
+		}
+	}
+
+	/// One level less urgent, dropping the priority altogether below `P3`.
+	pub fn lower(priority: Option<Self>) -> Option<Self> {
+		Self::ALL.get(priority? as usize + 1).copied()
+	}
+}
+
+impl std::str::FromStr for Priority {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL.into_iter().find(|priority| priority.to_string().eq_ignore_ascii_case(s)).ok_or_else(|| format!("expected a priority from P0 to P3, found {s:?}"))
+	}
+}
+
+impl std::fmt::Display for Priority {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "P{}", *self as usize)
+	}
+}
+
+/// How much work a tissue is expected to be, stored as a number of minutes or a size name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Estimate {
+	Minutes(u32),
+	Size(Size),
+}
+
+impl std::str::FromStr for Estimate {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(size) = Size::ALL.into_iter().find(|size| size.to_string().eq_ignore_ascii_case(s)) {
+			return Ok(Estimate::Size(size));
+		}
+		s.parse::<time::Span>()
+			.ok()
+			.and_then(|span| u32::try_from(span.0.num_minutes()).ok())
+			.map(Estimate::Minutes)
+			.ok_or_else(|| format!("expected a length of time such as 90m or a size from XS to XL, found {s:?}"))
+	}
+}
+
+impl std::fmt::Display for Estimate {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Estimate::Minutes(minutes) => write!(f, "{}", time::Span(TimeDelta::minutes((*minutes).into()))),
+			Estimate::Size(size) => write!(f, "{size}"),
+		}
+	}
+}
+
+/// A t-shirt size, for estimating without committing to a length of time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Size {
+	XS,
+	S,
+	M,
+	L,
+	XL,
+}
+
+impl Size {
+	pub const ALL: [Size; 5] = [Size::XS, Size::S, Size::M, Size::L, Size::XL];
+}
+
+impl std::fmt::Display for Size {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Size::XS => "XS",
+			Size::S => "S",
+			Size::M => "M",
+			Size::L => "L",
+			Size::XL => "XL",
+		})
+	}
+}
+
+/// Limits past which a tissue ought to be published as a full issue, from the `[config.too_big]` table.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TooBig {
+	/// Estimates longer than this many minutes.
+	pub minutes: u32,
+	/// Estimates of this size or larger.
+	pub size: Size,
+	/// Tissues open for more than this many days.
+	pub days: u32,
+	/// Tissues with more description items than this.
+	pub descriptions: usize,
+}
+
+impl Default for TooBig {
+	fn default() -> Self {
+		Self {
+			minutes: 120,
+			size: Size::L,
+			days: 14,
+			descriptions: 8,
+		}
+	}
+}
+
+/// Why a tissue looks too big to finish in a single commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Oversized {
+	Estimate(Estimate),
+	Age(i64),
+	Descriptions(usize),
+}
+
+impl std::fmt::Display for Oversized {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Oversized::Estimate(estimate) => write!(f, "estimated at {estimate}"),
+			Oversized::Age(days) => write!(f, "open for {days} days"),
+			Oversized::Descriptions(count) => write!(f, "{count} description items"),
+		}
+	}
+}
+
+/// Orders for listing tissues, which leave the order they are stored in alone.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sort {
+	/// Most urgent first, with tissues that have no priority last
+	Priority,
+	/// Oldest first
+	Created,
+	/// Most recently changed first
+	Updated,
+	/// Alphabetically
+	Title,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Branch {
+	pub name: String,
+	/// The branch that was checked out when work started, which `finish` returns to.
+	pub base: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Resolution {
+	Committed {
+		hash: String,
+	},
+	Published {
+		url: String,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		number: Option<u64>,
+	},
+	Discarded,
+}
+
+impl From<publish::Issue> for Resolution {
+	fn from(publish::Issue { url, number, .. }: publish::Issue) -> Self {
+		Self::Published { url, number }
+	}
+}
+
+impl std::fmt::Display for Resolution {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Resolution::Committed { hash } => write!(f, "committed in {}", hash.get(..7).unwrap_or(hash)),
+			Resolution::Published { url, .. } => write!(f, "published to {url}"),
+			Resolution::Discarded => write!(f, "discarded"),
+		}
+	}
 }
 
 impl Tissue {
 	pub fn describe(&mut self, description: String) {
-		self.description.push(description);
+		self.description.push(description.into());
+	}
+
+	/// How many description items are checked off, and how many there are.
+	pub fn progress(&self) -> (usize, usize) {
+		(self.description.iter().filter(|item| item.done).count(), self.description.len())
 	}
 
 	pub fn tag(&mut self, tag: String) {
 		self.tags.insert(tag);
 	}
 
-	pub fn publish(&self) -> io::Result<()> {
-		let output = std::process::Command::new("gh").args(["label", "list"]).output()?;
-		if output.status.success() {
-			let labels = String::from_utf8_lossy(&output.stdout);
-			let labels = labels.lines().map(|s| s.split_once('\t').unwrap_or_default().0).collect::<Vec<_>>();
-			for tag in &self.tags {
-				if !labels.contains(&tag.as_str()) {
-					let output = std::process::Command::new("gh").args(["label", "create", tag]).output()?;
-					if !output.status.success() {
-						return Err(io::Error::other(String::from_utf8_lossy(&output.stderr)));
-					}
-				}
-			}
+	pub fn is_overdue(&self, today: NaiveDate) -> bool {
+		// Committed tissues kept for history are never late.
+		self.resolution.is_none() && self.due.is_some_and(|due| due < today)
+	}
+
+	/// Edit this tissue's notes in the user's editor, through a temporary markdown file.
+	pub fn edit_notes(&mut self) -> io::Result<()> {
+		let path = std::env::temp_dir().join(format!("tissue-{}-{}.md", std::process::id(), self.id));
+		fs::write(&path, &self.notes)?;
+		let status = editor().arg(&path).status();
+		let notes = fs::read_to_string(&path);
+		let _ = fs::remove_file(&path);
+		let status = status?;
+		if !status.success() {
+			return Err(io::Error::other(format!("editor exited with {status}")));
+		}
+		self.notes = notes?.trim_end().into();
+		Ok(())
+	}
+
+	/// Each way this tissue is past the `too_big` limits, as of `now`.
+	///
+	/// Tissues that are closed or already linked to an issue are never too big.
+	pub fn oversized(&self, too_big: &TooBig, now: DateTime<Utc>) -> Vec<Oversized> {
+		let mut oversized = Vec::new();
+		if self.resolution.is_some() || self.issue.is_some() {
+			return oversized;
+		}
+		match self.estimate {
+			Some(estimate @ Estimate::Minutes(minutes)) if minutes > too_big.minutes => oversized.push(Oversized::Estimate(estimate)),
+			Some(estimate @ Estimate::Size(size)) if size >= too_big.size => oversized.push(Oversized::Estimate(estimate)),
+			_ => {}
+		}
+		if let Some(days) = self.created.map(|created| (now - created).num_days()).filter(|&days| days > too_big.days.into()) {
+			oversized.push(Oversized::Age(days));
+		}
+		if self.description.len() > too_big.descriptions {
+			oversized.push(Oversized::Descriptions(self.description.len()));
+		}
+		oversized
+	}
+
+	/// Whether this tissue's timer is running.
+	pub fn is_running(&self) -> bool {
+		self.work.last().is_some_and(|interval| interval.end.is_none())
+	}
+
+	/// How long has been spent on this tissue after `since`, counting a running timer up to `now`.
+	pub fn tracked(&self, since: Option<DateTime<Utc>>, now: DateTime<Utc>) -> TimeDelta {
+		self.work.iter().map(|interval| interval.duration(since, now)).sum()
+	}
+
+	fn stop_timer(&mut self, now: DateTime<Utc>) {
+		if let Some(interval) = self.work.last_mut().filter(|interval| interval.end.is_none()) {
+			interval.end = Some(now);
+		}
+	}
+
+	/// Whether this tissue is due by `today + span`, including if it is already overdue.
+	///
+	/// Every date is within a span too long to add to `today`.
+	pub fn is_due_within(&self, today: NaiveDate, span: time::Span) -> bool {
+		self.due.is_some_and(|due| today.checked_add_signed(span.0).is_none_or(|last| due <= last))
+	}
+
+	/// Publish this tissue to `backend`, or the configured backend if none is given, and link it to the new issue.
+	pub fn publish(&mut self, config: &publish::Config, backend: Option<publish::Backend>) -> publish::Result<&publish::Issue> {
+		let issue = config.execute(&config.plan(self, backend)?, backend)?;
+		Ok(self.link(issue))
+	}
+
+	/// Link this tissue to the issue it was just published as, so later changes can be synced.
+	pub fn link(&mut self, issue: publish::Issue) -> &publish::Issue {
+		self.synced = Some(sync::Synced::of(self));
+		self.issue.insert(issue)
+	}
+
+	/// The message used when committing this tissue, with a trailer for the time spent on it if configured.
+	pub fn commit_message(&self, config: &Config) -> String {
+		let tracked = self.tracked(None, Utc::now());
+		if config.time_trailer && tracked > TimeDelta::zero() {
+			format!("{}\n\nTime-spent: {}", self.title, time::Span(tracked))
 		} else {
-			return Err(io::Error::other(String::from_utf8_lossy(&output.stderr)));
+			self.title.clone()
 		}
+	}
 
-		let output = std::process::Command::new("gh")
-			.args(["issue", "create"])
-			.args(["--title", &self.title])
-			.args(["--body", &self.description.join("\n")])
-			.args(["--label", &self.tags.iter().fold(String::new(), |a, b| a + "\n" + b)])
-			.output()?;
-		if output.status.success() {
-			Ok(())
+	/// A branch name derived from the title, such as `fix-the-thing` for "Fix the thing!".
+	pub fn slug(&self) -> String {
+		let slug = self.title.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+		if slug.is_empty() {
+			"tissue".into()
 		} else {
-			Err(io::Error::other(String::from_utf8_lossy(&output.stderr)))
+			slug
 		}
 	}
 
-	pub fn commit(&self) -> io::Result<()> {
-		let output = std::process::Command::new("git").arg("add").arg("--all").output()?;
-		if output.status.success() {
-			let output = std::process::Command::new("git").arg("commit").arg("-m").arg(&self.title).output()?;
-			if output.status.success() {
-				Ok(())
-			} else {
-				Err(io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()))
-			}
+	/// Create and check out a branch for this tissue, remembering the current branch as its base.
+	pub fn start_branch(&mut self) -> io::Result<()> {
+		let base = git(&["branch", "--show-current"])?;
+		if base.is_empty() {
+			return Err(io::Error::other("HEAD is detached, so there is no branch to return to when finishing"));
+		}
+		let name = self.slug();
+		git(&["checkout", "-b", &name])?;
+		self.branch = Some(Branch { name, base });
+		Ok(())
+	}
+
+	/// Return to the base branch and bring this tissue's branch into it using [`Self::commit_message`].
+	///
+	/// Squashes the branch into a single commit unless `merge` is set.
+	/// If the merge fails, it is undone and the branch that was checked out before is checked out again.
+	/// Returns the hash of the new commit.
+	pub fn finish_branch(&mut self, merge: bool, config: &Config, force: bool) -> Result<String, CommitError> {
+		self.check_committable(config, force)?;
+		let Some(Branch { name, base }) = &self.branch else {
+			return Err(io::Error::other(format!("\"{}\" has no branch", self.title)).into());
+		};
+		let previous = git(&["branch", "--show-current"])?;
+		git(&["checkout", base])?;
+		let merged = if merge {
+			git(&["merge", "--no-ff", "-m", &self.commit_message(config), name])
 		} else {
-			Err(io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()))
+			git(&["merge", "--squash", name]).and_then(|_| git(&["commit", "-m", &self.commit_message(config)]))
+		};
+		if let Err(e) = merged {
+			// Errors here would hide the one that matters.
+			let _ = git(&["reset", "--merge"]);
+			let _ = git(&["checkout", if previous.is_empty() { name } else { &previous }]);
+			return Err(e.into());
+		}
+		let hash = git(&["rev-parse", "HEAD"])?;
+		// The commit has landed, so a branch left behind is no reason to fail.
+		// Squashed branches don't look merged to git, so they must be force-deleted.
+		if let Err(e) = git(&["branch", if merge { "-d" } else { "-D" }, name]) {
+			tracing::warn!("failed to delete branch {name}: {e}");
+		}
+		self.branch = None;
+		Ok(hash)
+	}
+
+	/// Returns the hash of the new commit.
+	///
+	/// With `require_checked` set, refuses to commit while items are unchecked unless `force` is set.
+	pub fn commit(&self, config: &Config, force: bool) -> Result<String, CommitError> {
+		self.check_committable(config, force)?;
+		git(&["add", "--all"])?;
+		git(&["commit", "-m", &self.commit_message(config)])?;
+		Ok(git(&["rev-parse", "HEAD"])?)
+	}
+
+	fn check_committable(&self, config: &Config, force: bool) -> Result<(), CommitError> {
+		let (done, total) = self.progress();
+		if config.require_checked && done < total && !force {
+			return Err(CommitError::UncheckedItems(total - done));
 		}
+		Ok(())
 	}
 }
 
-impl std::fmt::Display for Tissue {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let Tissue { title, description, tags } = self;
+/// Why a tissue couldn't be committed.
+#[derive(Debug, thiserror::Error)]
+pub enum CommitError {
+	/// Carries how many items are unchecked while `require_checked` is set.
+	#[error("{0} unchecked items")]
+	UncheckedItems(usize),
+	#[error(transparent)]
+	Git(#[from] io::Error),
+}
+
+/// Why tissues couldn't be purged.
+#[derive(Debug, thiserror::Error)]
+pub enum PurgeError {
+	#[error("no tissue with index {0} in the recycle bin")]
+	TissueNotFound(usize),
+	/// Carries how many tissues were purged before the error.
+	#[error("{1}")]
+	Io(usize, io::Error),
+}
+
+/// Run git, returning its trimmed standard output.
+fn git(args: &[&str]) -> io::Result<String> {
+	let output = std::process::Command::new("git").args(args).output()?;
+	if output.status.success() {
+		Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+	} else {
+		// Some failures, such as merge conflicts, are only explained on standard output.
+		let message = if output.stderr.is_empty() { &output.stdout } else { &output.stderr };
+		Err(io::Error::other(String::from_utf8_lossy(message).trim().to_string()))
+	}
+}
+
+/// Quote `s` for a POSIX shell, if it needs it.
+pub(crate) fn shell_quote(s: &str) -> String {
+	if !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=+@%,".contains(c)) {
+		s.into()
+	} else {
+		format!("'{}'", s.replace('\'', "'\\''"))
+	}
+}
+
+/// The name of the currently checked out branch, if any.
+pub fn current_branch() -> Option<String> {
+	git(&["branch", "--show-current"]).ok().filter(|branch| !branch.is_empty())
+}
+
+impl Tissue {
+	/// Write this tissue with every line after the first indented by `indent`.
+	fn write(&self, f: &mut std::fmt::Formatter<'_>, indent: &str, (done, total): (usize, usize)) -> std::fmt::Result {
+		let Tissue { title, description, tags, priority, .. } = self;
+		if let Some(priority) = priority {
+			write!(f, "[{priority}] ")?;
+		}
 		write!(f, "{title}")?;
+		if total > 0 {
+			write!(f, " [{done}/{total}]")?;
+		}
+		if let Some(estimate) = self.estimate {
+			write!(f, " ~{estimate}")?;
+		}
 		if !tags.is_empty() {
-			let tags = tags.iter().cloned().collect::<Vec<String>>().join(", ");
+			let tags = tags.iter().map(String::as_str).collect::<Vec<_>>().join(", ");
 			write!(f, " ({tags})",)?;
 		}
 		writeln!(f)?;
 		for description in description {
-			writeln!(f, "  - {description}")?;
+			writeln!(f, "{indent}  - {description}")?;
+		}
+		if let Some(due) = self.due {
+			writeln!(f, "{indent}  ! due {due}")?;
+		}
+		for reference in &self.references {
+			writeln!(f, "{indent}  @ {reference}")?;
+		}
+		for attachment in &self.attachments {
+			writeln!(f, "{indent}  + {attachment}")?;
+		}
+		if let Some(issue) = &self.issue {
+			writeln!(f, "{indent}  # {}", issue.url)?;
 		}
 		Ok(())
 	}
 }
 
+impl std::fmt::Display for Tissue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.write(f, "", self.progress())
+	}
+}
+
+/// An open tissue listed with its index, indented under its parent and with its subtasks' progress rolled up.
+pub struct Entry<'a> {
+	tissue_box: &'a TissueBox,
+	index: usize,
+	status_width: Option<usize>,
+}
+
+impl std::fmt::Display for Entry<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let indent = "  ".repeat(ancestors(&self.tissue_box.tissues, self.index).len());
+		write!(f, "{indent}{}. ", self.index)?;
+		if let (Some(width), Some(status)) = (self.status_width, self.tissue_box.status(self.index)) {
+			write!(f, "{status:width$} | ")?;
+		}
+		self.tissue_box.tissues[self.index].write(f, &indent, self.tissue_box.progress(self.index))?;
+		let blockers = self.tissue_box.blockers(self.index);
+		if !blockers.is_empty() {
+			writeln!(f, "{indent}  < blocked by {}", blockers.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+/// Indices of the open ancestors of the tissue at `index`, nearest first.
+pub fn ancestors(tissues: &[Tissue], index: usize) -> Vec<usize> {
+	let mut ancestors = Vec::new();
+	let mut parent = tissues[index].parent;
+	while let Some(id) = parent {
+		let Some(index) = tissues.iter().position(|tissue| tissue.id == id) else {
+			break;
+		};
+		// Guard against hand-edited boxes with cycles.
+		if ancestors.contains(&index) {
+			break;
+		}
+		ancestors.push(index);
+		parent = tissues[index].parent;
+	}
+	ancestors
+}
+
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct TissueBox {
 	#[serde(default)]
@@ -97,41 +864,512 @@ pub struct TissueBox {
 	tissues: Vec<Tissue>,
 	#[serde(default)]
 	starred: Option<usize>,
+	/// The ID given to the next new tissue.
+	#[serde(default)]
+	next_id: u64,
+	#[serde(default, skip_serializing_if = "Config::is_default")]
+	config: Config,
+	/// The tissues as they were last opened or saved, to tell which have been updated since.
+	#[serde(skip)]
+	saved: Vec<Tissue>,
+	/// Where the tissue box was last opened from or saved to.
+	#[serde(skip)]
+	path: Option<PathBuf>,
+	/// Descriptions and colors of tags, by name.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	tags: BTreeMap<String, TagInfo>,
+}
+
+/// Per-box settings, edited by hand in the `[config]` table.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Comment markers imported by `tissue scan`.
+	pub markers: Vec<String>,
+	/// Refuse to commit tissues with unchecked items, unless forced.
+	pub require_checked: bool,
+	/// Add a `Time-spent` trailer to commit messages of tissues with tracked time.
+	pub time_trailer: bool,
+	pub too_big: TooBig,
+	/// The largest file that can be attached to a tissue, in bytes.
+	pub attachment_limit: u64,
+	pub publish: publish::Config,
+	pub workflow: workflow::Workflow,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			markers: ["TODO", "FIXME", "HACK", "XXX"].map(String::from).to_vec(),
+			require_checked: false,
+			time_trailer: false,
+			too_big: TooBig::default(),
+			attachment_limit: 1 << 20,
+			publish: publish::Config::default(),
+			workflow: workflow::Workflow::default(),
+		}
+	}
+}
+
+impl Config {
+	fn is_default(&self) -> bool {
+		*self == Self::default()
+	}
 }
 
 impl TissueBox {
 	pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
-		toml::from_str(&fs::read_to_string(path.as_ref())?).map_err(io::Error::other)
+		let mut tissue_box: Self = toml::from_str(&fs::read_to_string(path.as_ref())?).map_err(io::Error::other)?;
+		// Boxes saved before tissues had IDs get them now.
+		tissue_box.next_id = tissue_box.next_id.max(tissue_box.tissues.iter().chain(&tissue_box.recycle_bin).map(|tissue| tissue.id + 1).max().unwrap_or(1));
+		for tissue in tissue_box.tissues.iter_mut().chain(&mut tissue_box.recycle_bin).filter(|tissue| tissue.id == 0) {
+			tissue.id = tissue_box.next_id;
+			tissue_box.next_id += 1;
+		}
+		tissue_box.saved = tissue_box.tissues.clone();
+		tissue_box.path = Some(path.as_ref().into());
+		Ok(tissue_box)
 	}
 
-	pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
-		fs::write(path.as_ref(), toml::to_string(self).map_err(io::Error::other)?)
+	/// Save the tissue box, first marking every tissue that changed since it was opened or last saved as updated.
+	pub fn save(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+		let now = Utc::now();
+		for tissue in &mut self.tissues {
+			if !self.saved.contains(tissue) {
+				tissue.updated = Some(now);
+			}
+		}
+		fs::write(path.as_ref(), toml::to_string(self).map_err(io::Error::other)?)?;
+		self.saved = self.tissues.clone();
+		self.path = Some(path.as_ref().into());
+		Ok(())
+	}
+
+	pub fn config(&self) -> &Config {
+		&self.config
+	}
+
+	pub fn config_mut(&mut self) -> &mut Config {
+		&mut self.config
 	}
 
 	pub fn create(&mut self, title: String) {
-		self.tissues.push(Tissue { title, ..Default::default() })
+		self.add(Tissue { title, ..Default::default() })
+	}
+
+	/// Put a new tissue in the box, recording when it was created.
+	pub fn add(&mut self, tissue: Tissue) {
+		let tissue = self.new_tissue(tissue);
+		self.tissues.push(tissue)
+	}
+
+	/// Give a tissue about to be put in the box an ID and creation time.
+	fn new_tissue(&mut self, tissue: Tissue) -> Tissue {
+		// IDs start at 1, leaving 0 for tissues that don't have one yet.
+		self.next_id = self.next_id.max(1);
+		let id = self.next_id;
+		self.next_id += 1;
+		Tissue { id, created: Some(Utc::now()), ..tissue }
+	}
+
+	/// Turn each unchecked item of a tissue's description into a subtask, returning how many were made.
+	///
+	/// Subtasks are placed after the tissue and any subtasks it already has. Checked items stay where they are.
+	pub fn split(&mut self, index: usize) -> Option<usize> {
+		let tissue = self.tissues.get_mut(index)?;
+		let (done, todo): (Vec<_>, Vec<_>) = std::mem::take(&mut tissue.description).into_iter().partition(|item| item.done);
+		tissue.description = done;
+		let parent = tissue.id;
+		let mut position = index + 1;
+		while position < self.tissues.len() && ancestors(&self.tissues, position).contains(&index) {
+			position += 1;
+		}
+		let count = todo.len();
+		for (offset, item) in todo.into_iter().enumerate() {
+			let subtask = self.new_tissue(Tissue {
+				title: item.text,
+				parent: Some(parent),
+				..Default::default()
+			});
+			self.tissues.insert(position + offset, subtask);
+		}
+		if let Some(starred) = &mut self.starred {
+			if *starred >= position {
+				*starred += count;
+			}
+		}
+		Some(count)
+	}
+
+	/// How many description items and subtasks of the tissue at `index` are done, and how many there are.
+	///
+	/// Committed and published subtasks count as done, and discarded ones don't count at all.
+	pub fn progress(&self, index: usize) -> (usize, usize) {
+		let tissue = &self.tissues[index];
+		let (mut done, mut total) = tissue.progress();
+		for subtask in self.tissues.iter().chain(&self.recycle_bin).filter(|subtask| subtask.parent == Some(tissue.id)) {
+			match subtask.resolution {
+				None => total += 1,
+				Some(Resolution::Discarded) => {}
+				Some(_) => {
+					done += 1;
+					total += 1;
+				}
+			}
+		}
+		(done, total)
+	}
+
+	/// Indices of the open tissues blocking the tissue at `index`.
+	///
+	/// Tissues that have been closed or committed no longer block anything.
+	pub fn blockers(&self, index: usize) -> Vec<usize> {
+		let blocked_by = &self.tissues[index].blocked_by;
+		self.tissues.iter().enumerate().filter(|(_, tissue)| tissue.resolution.is_none() && blocked_by.contains(&tissue.id)).map(|(index, _)| index).collect()
+	}
+
+	/// Whether the tissue at `index` waits on the one at `other`, directly or through other tissues.
+	pub fn depends_on(&self, index: usize, other: usize) -> bool {
+		let target = self.tissues[other].id;
+		let mut seen = HashSet::new();
+		let mut pending = self.tissues[index].blocked_by.clone();
+		while let Some(id) = pending.pop() {
+			if id == target {
+				return true;
+			}
+			// Closed tissues are followed too, since they may be restored.
+			if seen.insert(id) {
+				if let Some(tissue) = self.tissues.iter().chain(&self.recycle_bin).find(|tissue| tissue.id == id) {
+					pending.extend(&tissue.blocked_by);
+				}
+			}
+		}
+		false
+	}
+
+	/// The workflow status of the tissue at `index`.
+	pub fn status(&self, index: usize) -> Option<&str> {
+		self.tissues[index].status.as_deref().or_else(|| self.config.workflow.initial())
+	}
+
+	/// Move the tissue at `index` to another status, if the workflow allows it.
+	///
+	/// Moving a committed tissue kept for history out of the last status reopens it.
+	pub fn set_status(&mut self, index: usize, status: &str) -> workflow::Result<()> {
+		let workflow = &self.config.workflow;
+		let tissue = &mut self.tissues[index];
+		workflow.check(tissue.status.as_deref().or_else(|| workflow.initial()), status)?;
+		if workflow.done() != Some(status) {
+			tissue.resolution = None;
+		}
+		tissue.status = Some(status.into());
+		Ok(())
+	}
+
+	/// Record that the tissue at `index` was committed as `hash`.
+	///
+	/// With `keep_history` set, it stays in the box in the workflow's last status; otherwise it goes to the recycle bin.
+	pub fn close_committed(&mut self, index: usize, hash: String) -> Option<()> {
+		let workflow = &self.config.workflow;
+		match workflow.done() {
+			Some(done) if workflow.keep_history => {
+				let tissue = self.tissues.get_mut(index)?;
+				tissue.status = Some(done.into());
+				tissue.resolution = Some(Resolution::Committed { hash });
+				tissue.stop_timer(Utc::now());
+				if self.starred == Some(index) {
+					self.starred = None;
+				}
+			}
+			_ => {
+				self.close(index, Resolution::Committed { hash })?;
+			}
+		}
+		Some(())
+	}
+
+	/// Every tag that is used by an open tissue or has been described, with how many open tissues use it.
+	pub fn tag_counts(&self) -> BTreeMap<&str, usize> {
+		let mut counts = self.tags.keys().map(|tag| (tag.as_str(), 0)).collect::<BTreeMap<_, _>>();
+		for tag in self.tissues.iter().flat_map(|tissue| &tissue.tags) {
+			*counts.entry(tag).or_default() += 1;
+		}
+		counts
+	}
+
+	/// Every tag on an open or removed tissue, or that has been described, possibly more than once.
+	fn known_tags(&self) -> impl Iterator<Item = &str> {
+		self.tissues.iter().chain(&self.recycle_bin).flat_map(|tissue| &tissue.tags).chain(self.tags.keys()).map(String::as_str)
+	}
+
+	/// Whether `tag` is used by any tissue, open or not, or has been described.
+	pub fn has_tag(&self, tag: &str) -> bool {
+		self.known_tags().any(|known| known == tag)
+	}
+
+	/// Check that `tag` is valid and doesn't differ only by case from a known tag, other than those in `replacing`,
+	/// since GitHub would read both as the same label.
+	pub fn check_new_tag(&self, tag: &str, replacing: &[String]) -> Result<(), String> {
+		check_tag(tag)?;
+		let lowercase = tag.to_lowercase();
+		match self.known_tags().find(|known| *known != tag && known.to_lowercase() == lowercase && !replacing.iter().any(|replaced| replaced == known)) {
+			Some(known) => Err(format!("tag {tag:?} differs from {known:?} only by case")),
+			None => Ok(()),
+		}
+	}
+
+	pub fn tag_info(&self, tag: &str) -> Option<&TagInfo> {
+		self.tags.get(tag)
+	}
+
+	/// Set the description and color of `tag`, forgetting them if both are unset.
+	pub fn set_tag_info(&mut self, tag: &str, info: TagInfo) {
+		if info.is_empty() {
+			self.tags.remove(tag);
+		} else {
+			self.tags.insert(tag.into(), info);
+		}
+	}
+
+	/// Replace each of `tags` with `into` on every tissue, open or not, returning how many tissues changed.
+	///
+	/// `into` keeps its own description and color, or takes the first it finds among `tags`.
+	pub fn merge_tags(&mut self, tags: &[String], into: &str) -> usize {
+		let mut changed = 0;
+		for tissue in self.tissues.iter_mut().chain(&mut self.recycle_bin) {
+			let before = tissue.tags.len();
+			tissue.tags.retain(|tag| !tags.contains(tag) || tag == into);
+			if tissue.tags.len() != before {
+				tissue.tags.insert(into.into());
+				changed += 1;
+			}
+		}
+		for tag in tags.iter().filter(|tag| *tag != into) {
+			if let Some(info) = self.tags.remove(tag) {
+				self.tags.entry(into.into()).or_insert(info);
+			}
+		}
+		changed
+	}
+
+	/// Where files attached to the tissue with `id` are kept, beside the tissue box file, such as `.tissuebox.d/3`.
+	///
+	/// Unknown until the tissue box has been opened or saved.
+	pub fn attachment_dir(&self, id: u64) -> Option<PathBuf> {
+		let mut dir = self.path.as_ref()?.as_os_str().to_owned();
+		dir.push(".d");
+		Some(PathBuf::from(dir).join(id.to_string()))
+	}
+
+	/// Attach a URL, or a copy of the file at a path, to the tissue at `index`.
+	pub fn attach(&mut self, index: usize, target: &str) -> io::Result<&Attachment> {
+		let attachment = if Attachment::is_link(target) {
+			Attachment::Link { url: target.into() }
+		} else {
+			let source = Path::new(target);
+			let size = fs::metadata(source)?.len();
+			if size > self.config.attachment_limit {
+				return Err(io::Error::other(format!("{target} is {size} bytes, over the attachment limit of {}", self.config.attachment_limit)));
+			}
+			let name = source.file_name().ok_or_else(|| io::Error::other(format!("{target} is not a file")))?.to_string_lossy().into_owned();
+			let dir = self.attachment_dir(self.tissues[index].id).ok_or_else(|| io::Error::other("the tissue box must be saved before attaching files"))?;
+			let destination = dir.join(&name);
+			if destination.try_exists()? {
+				return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", destination.display())));
+			}
+			fs::create_dir_all(&dir)?;
+			fs::copy(source, &destination)?;
+			Attachment::File { name }
+		};
+		let attachments = &mut self.tissues[index].attachments;
+		attachments.push(attachment);
+		Ok(attachments.last().expect("attachment was just pushed"))
+	}
+
+	/// What to open for an attachment of `tissue`: its URL, or the path of its copy.
+	pub fn attachment_target(&self, tissue: &Tissue, attachment: &Attachment) -> Option<OsString> {
+		match attachment {
+			Attachment::Link { url } => Some(url.into()),
+			Attachment::File { name } => Some(self.attachment_dir(tissue.id)?.join(name).into()),
+		}
+	}
+
+	/// Permanently delete the tissue at `index` in the recycle bin, or all of them, along with their attachments.
+	///
+	/// Returns how many tissues were deleted.
+	/// Tissues whose attachments couldn't be deleted stay in the recycle bin.
+	pub fn purge(&mut self, index: Option<usize>) -> Result<usize, PurgeError> {
+		let indices = match index {
+			Some(index) => {
+				self.recycle_bin.get(index).ok_or(PurgeError::TissueNotFound(index))?;
+				vec![index]
+			}
+			None => (0..self.recycle_bin.len()).collect(),
+		};
+		let mut error = None;
+		let mut purged = Vec::new();
+		for index in indices {
+			let removed = self.attachment_dir(self.recycle_bin[index].id).map_or(Ok(()), fs::remove_dir_all);
+			match removed {
+				Err(e) if e.kind() != io::ErrorKind::NotFound => {
+					error.get_or_insert(e);
+				}
+				_ => purged.push(index),
+			}
+		}
+		// Remove from the end so the remaining indices stay valid.
+		for &index in purged.iter().rev() {
+			self.recycle_bin.remove(index);
+		}
+		match error {
+			Some(e) => Err(PurgeError::Io(purged.len(), e)),
+			None => Ok(purged.len()),
+		}
+	}
+
+	/// The index of the tissue whose timer is running, if any.
+	pub fn running(&self) -> Option<usize> {
+		self.tissues.iter().position(Tissue::is_running)
+	}
+
+	/// Start timing the tissue at `index`, stopping whichever timer was running, and return the index of that tissue.
+	pub fn start_timer(&mut self, index: usize) -> Option<Option<usize>> {
+		self.tissues.get(index)?;
+		let now = Utc::now();
+		let stopped = self.stop_timer_at(now);
+		self.tissues[index].work.push(Interval { start: now, end: None });
+		Some(stopped)
+	}
+
+	/// Stop the running timer, returning the index of the tissue it was timing.
+	pub fn stop_timer(&mut self) -> Option<usize> {
+		self.stop_timer_at(Utc::now())
+	}
+
+	fn stop_timer_at(&mut self, now: DateTime<Utc>) -> Option<usize> {
+		let index = self.running()?;
+		self.tissues[index].stop_timer(now);
+		Some(index)
+	}
+
+	/// The open tissues at `indices`, in that order, ready to be listed.
+	pub fn entries(&self, indices: impl IntoIterator<Item = usize>) -> impl Iterator<Item = Entry<'_>> {
+		let status_width = self.status_width();
+		indices.into_iter().map(move |index| Entry { tissue_box: self, index, status_width })
+	}
+
+	/// How wide the column of statuses is when tissues are listed.
+	///
+	/// Statuses are only shown as a column once some tissue has left the first one, so until then there is none.
+	pub fn status_width(&self) -> Option<usize> {
+		if !self.tissues.iter().any(|tissue| tissue.status.is_some()) {
+			return None;
+		}
+		(0..self.tissues.len()).filter_map(|index| self.status(index)).map(str::len).max()
 	}
 
 	#[must_use]
 	pub fn remove(&mut self, index: usize) -> Option<Tissue> {
-		// If this issue is starred, reset the star state.
-		if let Some(i) = self.starred {
-			if i == index {
-				self.starred = None;
+		self.close(index, Resolution::Discarded)
+	}
+
+	/// Discard every tissue at `indices`, returning how many there were.
+	pub fn remove_all(&mut self, indices: &[usize]) -> usize {
+		let mut indices = indices.to_vec();
+		indices.sort();
+		indices.dedup();
+		// Remove from the end, so the indices of the rest stay put.
+		indices.into_iter().rev().filter(|&index| self.remove(index).is_some()).count()
+	}
+
+	/// Publish each planned tissue in turn, linking it to its new issue, and return the issues published.
+	///
+	/// Publishing stops at the first failure. Issues can't be taken back once created,
+	/// so the ones published before it stay linked, and the box must still be saved.
+	pub fn publish_all(&mut self, plans: &[(usize, publish::Plan)], backend: Option<publish::Backend>) -> (Vec<publish::Issue>, publish::Result<()>) {
+		let mut issues = Vec::new();
+		for (index, plan) in plans {
+			match self.config.publish.execute(plan, backend) {
+				Ok(issue) => issues.push(self.tissues[*index].link(issue).clone()),
+				Err(e) => return (issues, Err(e)),
 			}
 		}
+		(issues, Ok(()))
+	}
+
+	/// Move a tissue to the recycle bin, recording how it was resolved.
+	#[must_use]
+	pub fn close(&mut self, index: usize, resolution: Resolution) -> Option<Tissue> {
 		self.tissues.get(index)?;
-		let tissue = self.tissues.remove(index);
+		// If this issue is starred, reset the star state, or keep the star on its tissue as the ones after this shift up.
+		self.starred = match self.starred {
+			Some(i) if i == index => None,
+			Some(i) if i > index => Some(i - 1),
+			starred => starred,
+		};
+		let mut tissue = self.tissues.remove(index);
+		tissue.resolution = Some(resolution);
+		tissue.stop_timer(Utc::now());
 		self.recycle_bin.push(tissue.clone());
 		Some(tissue)
 	}
 
 	pub fn restore(&mut self, index: usize) -> Option<&Tissue> {
 		self.recycle_bin.get(index)?;
-		self.tissues.push(self.recycle_bin.remove(index));
+		let mut tissue = self.recycle_bin.remove(index);
+		tissue.resolution = None;
+		self.tissues.push(tissue);
 		self.tissues.last()
 	}
 
+	pub fn get_removed(&self, index: usize) -> Option<&Tissue> {
+		self.recycle_bin.get(index)
+	}
+
+	/// Format the recycle bin, including how each tissue was resolved.
+	pub fn trash(&self) -> String {
+		let mut out = String::new();
+		for (index, tissue) in self.recycle_bin.iter().enumerate() {
+			if let Some(resolution) = &tissue.resolution {
+				out += &format!("{index}. [{resolution}] {tissue}");
+			} else {
+				out += &format!("{index}. {tissue}");
+			}
+		}
+		out
+	}
+
+	pub fn star(&mut self, index: Option<usize>) {
+		self.starred = index;
+	}
+
+	pub fn starred(&self) -> Option<&Tissue> {
+		self.tissues.get(self.starred?)
+	}
+
+	/// Find the tissue being worked on in `branch`.
+	pub fn branch_owner(&self, branch: &str) -> Option<usize> {
+		self.tissues.iter().position(|tissue| tissue.branch.as_ref().is_some_and(|b| b.name == branch))
+	}
+
+	/// Find the tissue linked to the issue at `url`, whether open or removed.
+	pub fn find_issue(&self, url: &str) -> Option<&Tissue> {
+		self.tissues.iter().chain(&self.recycle_bin).find(|tissue| tissue.issue.as_ref().is_some_and(|issue| issue.url == url))
+	}
+
+	/// The open tissues and their indices, in `sort` order.
+	pub fn sorted(&self, sort: Sort) -> Vec<(usize, &Tissue)> {
+		let mut tissues = self.tissues.iter().enumerate().collect::<Vec<_>>();
+		match sort {
+			// `None` sorts before any priority, so compare the other way round to put it last.
+			Sort::Priority => tissues.sort_by_key(|(_, tissue)| std::cmp::Reverse(tissue.priority.map(std::cmp::Reverse))),
+			Sort::Created => tissues.sort_by_key(|(_, tissue)| tissue.created),
+			Sort::Updated => tissues.sort_by_key(|(_, tissue)| std::cmp::Reverse(tissue.updated.or(tissue.created))),
+			Sort::Title => tissues.sort_by_key(|(_, tissue)| tissue.title.to_lowercase()),
+		}
+		tissues
+	}
+
 	pub fn get(&self, index: usize) -> Option<&Tissue> {
 		self.tissues.get(index)
 	}
@@ -143,8 +1381,8 @@ impl TissueBox {
 
 impl std::fmt::Display for TissueBox {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		for (index, tissue) in self.tissues.iter().enumerate() {
-			write!(f, "{index}. {tissue}")?;
+		for entry in self.entries(0..self.tissues.len()) {
+			write!(f, "{entry}")?;
 		}
 		Ok(())
 	}
@@ -0,0 +1,384 @@
+//! Publishing tissues to issue trackers.
+
+mod gitea;
+mod github;
+mod gitlab;
+mod local;
+
+pub use gitea::Gitea;
+pub use github::GitHub;
+pub use gitlab::GitLab;
+pub use local::Local;
+
+use crate::{prelude::*, shell_quote, sync::Synced};
+use std::{path::PathBuf, process};
+
+pub trait Publisher {
+	/// Work out which labels must be created and which steps publishing `plan` involves, without changing anything.
+	fn plan(&self, plan: Plan) -> Result<Plan>;
+
+	/// Carry out a plan made by [`Self::plan`].
+	///
+	/// If a step fails, anything the earlier steps created is removed again.
+	fn execute(&self, plan: &Plan) -> Result<Issue>;
+
+	/// Fetch the current state of a published issue, for syncing.
+	fn fetch_issue(&self, issue: &Issue) -> io::Result<RemoteIssue> {
+		Err(unsupported(&issue.url))
+	}
+
+	/// Post a comment on a published issue.
+	fn comment(&self, issue: &Issue, _body: &str) -> io::Result<()> {
+		Err(unsupported(&issue.url))
+	}
+
+	/// Rename a published issue or change its labels.
+	fn update_issue(&self, issue: &Issue, _update: &Update) -> io::Result<()> {
+		Err(unsupported(&issue.url))
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("the {0} backend requires a [config.publish.{0}] table")]
+	MissingConfig(&'static str),
+	#[error("failed to plan: {0}")]
+	Plan(io::Error),
+	/// A tag that wouldn't survive being turned into a label.
+	#[error("{0}")]
+	InvalidLabel(String),
+	#[error("failed to create label {0:?}: {1}")]
+	CreateLabel(String, io::Error),
+	#[error("failed to create issue: {0}")]
+	CreateIssue(io::Error),
+	#[error("failed to set the type of {0}: {1}")]
+	SetIssueType(String, io::Error),
+	#[error("failed to write {}: {1}", .0.display())]
+	Write(PathBuf, io::Error),
+	#[error("{0}; rolling back left behind {}", .1.join(", "))]
+	RollbackFailed(Box<Error>, Vec<String>),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+type Undo<'a> = Box<dyn FnOnce() -> io::Result<()> + 'a>;
+
+/// Undoes the steps of [`Publisher::execute`] that succeeded when a later one fails.
+#[derive(Default)]
+struct Rollback<'a> {
+	undo: Vec<(String, Undo<'a>)>,
+}
+
+impl<'a> Rollback<'a> {
+	/// Remember how to remove `what`, which a step just created.
+	fn push(&mut self, what: String, undo: impl FnOnce() -> io::Result<()> + 'a) {
+		self.undo.push((what, Box::new(undo)));
+	}
+
+	/// Pass on the result of a step, or undo everything so far and fail with `error` if it failed.
+	fn check<T>(&mut self, result: io::Result<T>, error: impl FnOnce(io::Error) -> Error) -> Result<T> {
+		result.map_err(|e| {
+			let error = error(e);
+			let mut left = Vec::new();
+			for (what, undo) in self.undo.drain(..).rev() {
+				if let Err(e) = undo() {
+					left.push(format!("{what} ({e})"));
+				}
+			}
+			if left.is_empty() {
+				error
+			} else {
+				Error::RollbackFailed(Box::new(error), left)
+			}
+		})
+	}
+}
+
+fn unsupported(url: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::Unsupported, format!("this backend can't sync {url}"))
+}
+
+/// An issue on a tracker, created by publishing a tissue or imported from it.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Issue {
+	pub url: String,
+	/// The issue number, for trackers that have them.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub number: Option<u64>,
+	/// The tracker the issue is on, so it is synced with the same one.
+	pub backend: Backend,
+}
+
+impl Issue {
+	/// Take the issue number from the end of its URL, as in `https://github.com/owner/repo/issues/1`.
+	fn from_url(url: String, backend: Backend) -> Self {
+		let number = url.trim_end_matches('/').rsplit('/').next().and_then(|number| number.parse().ok());
+		Self { url, number, backend }
+	}
+}
+
+/// The contents of an issue fetched from a tracker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteIssue {
+	pub issue: Issue,
+	pub title: String,
+	pub body: String,
+	pub labels: Vec<String>,
+	pub closed: bool,
+}
+
+impl RemoteIssue {
+	/// Create a tissue linked back to this issue.
+	///
+	/// Each list item or paragraph line of the body becomes a description, checked off if it is a completed task,
+	/// stopping at a horizontal rule so footers such as the one [`body`] adds are left behind.
+	pub fn to_tissue(&self) -> Tissue {
+		let description: Vec<Item> = self
+			.body
+			.lines()
+			.map(str::trim)
+			.take_while(|line| *line != "---")
+			.filter(|line| !line.is_empty())
+			.map(|line| match ["- [x] ", "- [X] "].iter().find_map(|prefix| line.strip_prefix(prefix)) {
+				Some(text) => Item { text: text.into(), done: true },
+				None => ["- [ ] ", "- ", "* "].iter().find_map(|prefix| line.strip_prefix(prefix)).unwrap_or(line).to_string().into(),
+			})
+			.collect();
+		Tissue {
+			title: self.title.clone(),
+			tags: self.labels.iter().cloned().collect(),
+			issue: Some(self.issue.clone()),
+			synced: Some(Synced {
+				title: self.title.clone(),
+				descriptions: description.iter().map(|item| item.text.clone()).collect(),
+				labels: self.labels.clone(),
+			}),
+			description,
+			..Default::default()
+		}
+	}
+}
+
+/// Changes to make to a published issue when syncing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Update {
+	pub title: Option<String>,
+	pub add_labels: Vec<String>,
+	pub remove_labels: Vec<String>,
+}
+
+impl Update {
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+}
+
+/// Extra issue fields, set per tissue or as defaults in the `[config.publish.metadata]` table.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub assignees: Vec<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub milestone: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub project: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub issue_type: Option<String>,
+}
+
+impl Metadata {
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+
+	/// Fill in anything unset with `defaults`.
+	pub fn or(&self, defaults: &Self) -> Self {
+		Self {
+			assignees: if self.assignees.is_empty() { defaults.assignees.clone() } else { self.assignees.clone() },
+			milestone: self.milestone.clone().or_else(|| defaults.milestone.clone()),
+			project: self.project.clone().or_else(|| defaults.project.clone()),
+			issue_type: self.issue_type.clone().or_else(|| defaults.issue_type.clone()),
+		}
+	}
+}
+
+impl std::fmt::Display for Metadata {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if !self.assignees.is_empty() {
+			writeln!(f, "Assignees: {}", self.assignees.join(", "))?;
+		}
+		if let Some(milestone) = &self.milestone {
+			writeln!(f, "Milestone: {milestone}")?;
+		}
+		if let Some(project) = &self.project {
+			writeln!(f, "Project: {project}")?;
+		}
+		if let Some(issue_type) = &self.issue_type {
+			writeln!(f, "Type: {issue_type}")?;
+		}
+		Ok(())
+	}
+}
+
+/// How description lines are laid out in an issue body.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyStyle {
+	/// `- [ ] line`, so each line can be checked off on the tracker
+	#[default]
+	Tasks,
+	/// `- line`
+	Bullets,
+}
+
+/// Everything a publisher will send, for previewing before anything is created.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+	pub title: String,
+	pub body: String,
+	/// Labels that don't exist yet and will be created.
+	pub create_labels: Vec<String>,
+	/// Labels applied to the new issue.
+	pub labels: Vec<String>,
+	pub metadata: Metadata,
+	/// The commands or API calls that will run, in order.
+	pub steps: Vec<String>,
+}
+
+impl Plan {
+	/// Draft the issue for `tissue`, before any backend has planned it.
+	pub fn new(tissue: &Tissue, config: &Config) -> Self {
+		Self {
+			title: tissue.title.clone(),
+			body: body(tissue, config.body_style),
+			create_labels: Vec::new(),
+			labels: tissue.tags.iter().cloned().collect(),
+			metadata: tissue.metadata.or(&config.metadata),
+			steps: Vec::new(),
+		}
+	}
+}
+
+/// Format the markdown body of an issue for `tissue`.
+pub fn body(tissue: &Tissue, style: BodyStyle) -> String {
+	let mut body = list(&tissue.description, style);
+	if !tissue.references.is_empty() {
+		if !body.is_empty() {
+			body += "\n";
+		}
+		body += "### References\n";
+		for reference in &tissue.references {
+			body += &format!("- `{reference}`\n");
+		}
+	}
+	if !body.is_empty() {
+		body += "\n";
+	}
+	body += "---\n_Published from a tissue box._\n";
+	body
+}
+
+/// Format description items as a markdown list.
+pub fn list(descriptions: &[Item], style: BodyStyle) -> String {
+	let mut list = String::new();
+	for description in descriptions {
+		list += match style {
+			BodyStyle::Tasks if description.done => "- [x] ",
+			BodyStyle::Tasks => "- [ ] ",
+			BodyStyle::Bullets => "- ",
+		};
+		list += &description.text;
+		list += "\n";
+	}
+	list
+}
+
+impl std::fmt::Display for Plan {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "Title: {}", self.title)?;
+		writeln!(f, "Labels: {}", self.labels.join(", "))?;
+		writeln!(f, "Labels to create: {}", self.create_labels.join(", "))?;
+		write!(f, "{}", self.metadata)?;
+		writeln!(f, "Body:")?;
+		for line in self.body.lines() {
+			writeln!(f, "  {line}")?;
+		}
+		writeln!(f, "Steps:")?;
+		for step in &self.steps {
+			writeln!(f, "  {step}")?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+	/// GitHub, using the `gh` command
+	#[default]
+	Github,
+	/// GitLab, using the `glab` command
+	Gitlab,
+	/// Gitea or Forgejo, using its REST API
+	Gitea,
+	/// Markdown files in a local directory
+	Local,
+}
+
+/// Publishing settings, from the `[config.publish]` table.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// The backend used when none is given on the command line.
+	pub backend: Backend,
+	pub body_style: BodyStyle,
+	/// Defaults for tissues that don't set their own metadata.
+	#[serde(skip_serializing_if = "Metadata::is_empty")]
+	pub metadata: Metadata,
+	pub github: GitHub,
+	pub gitlab: GitLab,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub gitea: Option<Gitea>,
+	pub local: Local,
+}
+
+impl Config {
+	/// Construct the publisher for `backend`, or the configured backend if none is given.
+	pub fn publisher(&self, backend: Option<Backend>) -> Result<Box<dyn Publisher>> {
+		Ok(match backend.unwrap_or(self.backend) {
+			Backend::Github => Box::new(self.github.clone()),
+			Backend::Gitlab => Box::new(self.gitlab.clone()),
+			Backend::Gitea => Box::new(self.gitea.clone().ok_or(Error::MissingConfig("gitea"))?),
+			Backend::Local => Box::new(self.local.clone()),
+		})
+	}
+
+	/// Plan publishing `tissue` to `backend`, or the configured backend if none is given.
+	pub fn plan(&self, tissue: &Tissue, backend: Option<Backend>) -> Result<Plan> {
+		for tag in &tissue.tags {
+			check_tag(tag).map_err(Error::InvalidLabel)?;
+		}
+		self.publisher(backend)?.plan(Plan::new(tissue, self))
+	}
+
+	/// Carry out a plan made by [`Self::plan`] for the same backend.
+	pub fn execute(&self, plan: &Plan, backend: Option<Backend>) -> Result<Issue> {
+		self.publisher(backend)?.execute(plan)
+	}
+}
+
+/// Format a command the way it would be typed into a shell.
+fn describe(command: &process::Command) -> String {
+	std::iter::once(command.get_program()).chain(command.get_args()).map(|arg| shell_quote(&arg.to_string_lossy())).collect::<Vec<_>>().join(" ")
+}
+
+/// Run a command, returning its standard output or failing with its standard error.
+fn output(command: &mut process::Command) -> io::Result<String> {
+	let output = command.output()?;
+	if output.status.success() {
+		Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+	} else {
+		Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim_end().to_string()))
+	}
+}
@@ -0,0 +1,120 @@
+use super::{Backend, Error, Issue, Plan, Publisher, Result, Rollback};
+use crate::prelude::*;
+use serde_json::{json, Value};
+use std::{
+	io::Write,
+	process::{self, Stdio},
+};
+
+/// Publishes issues to a Gitea or Forgejo instance through its REST API, using `curl`.
+///
+/// Milestones, projects and issue types are ignored, since the API only accepts them by ID.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Gitea {
+	/// Base URL of the instance, such as `https://codeberg.org`.
+	pub url: String,
+	pub owner: String,
+	pub repo: String,
+	/// Environment variable holding an access token.
+	#[serde(default = "default_token_env")]
+	pub token_env: String,
+}
+
+fn default_token_env() -> String {
+	"GITEA_TOKEN".into()
+}
+
+/// Color given to labels created while publishing, since the API requires one.
+const LABEL_COLOR: &str = "#ededed";
+
+/// Escape a string for a double-quoted curl config value.
+fn escape(s: &str) -> String {
+	s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+impl Gitea {
+	fn endpoint(&self, endpoint: &str) -> String {
+		format!("{}/api/v1/repos/{}/{}/{endpoint}", self.url.trim_end_matches('/'), self.owner, self.repo)
+	}
+
+	fn request(&self, method: &str, endpoint: &str, body: Option<Value>) -> io::Result<Value> {
+		let url = self.endpoint(endpoint);
+		// The token and body are passed through curl's config on stdin to keep them out of the process list.
+		let mut config = String::new();
+		if let Ok(token) = std::env::var(&self.token_env) {
+			config += &format!("header = \"Authorization: token {}\"\n", escape(&token));
+		}
+		if let Some(body) = body {
+			config += &format!("data-binary = \"{}\"\n", escape(&body.to_string()));
+		}
+		let mut child = process::Command::new("curl")
+			.args(["--silent", "--show-error", "--fail-with-body", "--request", method])
+			.args(["--header", "Content-Type: application/json", "--header", "Accept: application/json"])
+			.args(["--config", "-", &url])
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()?;
+		child.stdin.take().expect("stdin is piped").write_all(config.as_bytes())?;
+		let output = child.wait_with_output()?;
+		if output.status.success() && output.stdout.is_empty() {
+			// Deleting responds with no content at all.
+			Ok(Value::Null)
+		} else if output.status.success() {
+			serde_json::from_slice(&output.stdout).map_err(io::Error::other)
+		} else {
+			Err(io::Error::other(format!("{method} {url}: {}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout))))
+		}
+	}
+
+	/// Fetch the names and IDs of every label in the repository.
+	fn labels(&self) -> io::Result<Vec<(String, u64)>> {
+		let mut labels = Vec::new();
+		for page in 1.. {
+			let Value::Array(page) = self.request("GET", &format!("labels?page={page}&limit=50"), None)? else {
+				return Err(io::Error::other("expected a list of labels"));
+			};
+			if page.is_empty() {
+				break;
+			}
+			labels.extend(page.iter().filter_map(|label| Some((label["name"].as_str()?.to_string(), label["id"].as_u64()?))));
+		}
+		Ok(labels)
+	}
+}
+
+impl Gitea {
+	fn issue(plan: &Plan, labels: serde_json::Value) -> Value {
+		json!({ "title": plan.title, "body": plan.body, "labels": labels, "assignees": plan.metadata.assignees })
+	}
+}
+
+impl Publisher for Gitea {
+	fn plan(&self, mut plan: Plan) -> Result<Plan> {
+		let existing = self.labels().map_err(Error::Plan)?;
+		plan.create_labels = plan.labels.iter().filter(|label| !existing.iter().any(|(name, _)| name == *label)).cloned().collect();
+		plan.steps = plan.create_labels.iter().map(|label| format!("POST {} {}", self.endpoint("labels"), json!({ "name": label, "color": LABEL_COLOR }))).collect();
+		// Labels are sent by ID, which new labels don't have until they are created, so the preview names them instead.
+		plan.steps.push(format!("POST {} {}", self.endpoint("issues"), Self::issue(&plan, json!(plan.labels))));
+		Ok(plan)
+	}
+
+	fn execute(&self, plan: &Plan) -> Result<Issue> {
+		let mut labels = self.labels().map_err(Error::Plan)?;
+		let mut rollback = Rollback::default();
+		for label in &plan.create_labels {
+			let created = self.request("POST", "labels", Some(json!({ "name": label, "color": LABEL_COLOR })));
+			let id = rollback.check(created.and_then(|created| created["id"].as_u64().ok_or_else(|| io::Error::other("created label has no id"))), |e| Error::CreateLabel(label.clone(), e))?;
+			rollback.push(format!("label {label:?}"), move || self.request("DELETE", &format!("labels/{id}"), None).map(drop));
+			labels.push((label.clone(), id));
+		}
+		let labels = plan.labels.iter().filter_map(|label| labels.iter().find(|(name, _)| name == label).map(|(_, id)| *id)).collect::<Vec<_>>();
+		let issue = rollback.check(self.request("POST", "issues", Some(Self::issue(plan, json!(labels)))), Error::CreateIssue)?;
+		let url = rollback.check(issue["html_url"].as_str().ok_or_else(|| io::Error::other("created issue has no URL")), Error::CreateIssue)?;
+		Ok(Issue {
+			url: url.to_string(),
+			number: issue["number"].as_u64(),
+			backend: Backend::Gitea,
+		})
+	}
+}
@@ -0,0 +1,184 @@
+use super::{describe, output, Backend, Error, Issue, Plan, Publisher, RemoteIssue, Result, Rollback, Update};
+use crate::prelude::*;
+use std::{path::PathBuf, process};
+
+/// Publishes issues using the GitHub CLI.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GitHub {
+	pub program: PathBuf,
+}
+
+impl Default for GitHub {
+	fn default() -> Self {
+		Self { program: "gh".into() }
+	}
+}
+
+impl GitHub {
+	fn existing_labels(&self) -> io::Result<Vec<String>> {
+		// gh only lists 30 labels unless told otherwise, and creating one that exists fails.
+		let existing = output(process::Command::new(&self.program).args(["label", "list", "--limit", "1000"]))?;
+		Ok(existing.lines().map(|s| s.split_once('\t').unwrap_or_default().0.to_string()).collect())
+	}
+
+	fn create_label(&self, label: &str) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["label", "create", label]);
+		command
+	}
+
+	fn create_issue(&self, plan: &Plan) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["issue", "create"]).args(["--title", &plan.title]).args(["--body", &plan.body]);
+		for label in &plan.labels {
+			command.args(["--label", label]);
+		}
+		for assignee in &plan.metadata.assignees {
+			command.args(["--assignee", assignee]);
+		}
+		if let Some(milestone) = &plan.metadata.milestone {
+			command.args(["--milestone", milestone]);
+		}
+		if let Some(project) = &plan.metadata.project {
+			command.args(["--project", project]);
+		}
+		command
+	}
+
+	fn delete_label(&self, label: &str) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["label", "delete", label, "--yes"]);
+		command
+	}
+
+	fn delete_issue(&self, url: &str) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["issue", "delete", url, "--yes"]);
+		command
+	}
+
+	/// `gh issue create` can't set an issue type, so it is set through the REST API afterwards.
+	fn set_issue_type(&self, number: &str, issue_type: &str) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["api", "--method", "PATCH", &format!("repos/{{owner}}/{{repo}}/issues/{number}"), "--field", &format!("type={issue_type}")]);
+		command
+	}
+}
+
+/// Fields requested from `gh` when fetching issues.
+const ISSUE_FIELDS: &str = "number,url,title,body,labels,state";
+
+#[derive(serde::Deserialize)]
+struct GhIssue {
+	number: u64,
+	url: String,
+	title: String,
+	#[serde(default)]
+	body: String,
+	#[serde(default)]
+	labels: Vec<GhLabel>,
+	#[serde(default)]
+	state: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GhLabel {
+	name: String,
+}
+
+impl From<GhIssue> for RemoteIssue {
+	fn from(issue: GhIssue) -> Self {
+		Self {
+			issue: Issue {
+				url: issue.url,
+				number: Some(issue.number),
+				backend: Backend::Github,
+			},
+			title: issue.title,
+			body: issue.body,
+			labels: issue.labels.into_iter().map(|label| label.name).collect(),
+			closed: issue.state == "CLOSED",
+		}
+	}
+}
+
+impl GitHub {
+	pub fn fetch(&self, number: u64) -> io::Result<RemoteIssue> {
+		let issue = output(process::Command::new(&self.program).args(["issue", "view", &number.to_string(), "--json", ISSUE_FIELDS]))?;
+		serde_json::from_str::<GhIssue>(&issue).map(Into::into).map_err(io::Error::other)
+	}
+
+	/// List up to `limit` open issues matching a search query, and only those assigned to the current user if `assigned_to_me` is set.
+	pub fn search(&self, query: Option<&str>, assigned_to_me: bool, limit: usize) -> io::Result<Vec<RemoteIssue>> {
+		let mut command = process::Command::new(&self.program);
+		command.args(["issue", "list", "--json", ISSUE_FIELDS, "--limit", &limit.to_string()]);
+		if let Some(query) = query {
+			command.args(["--search", query]);
+		}
+		if assigned_to_me {
+			command.args(["--assignee", "@me"]);
+		}
+		let issues = output(&mut command)?;
+		serde_json::from_str::<Vec<GhIssue>>(&issues).map(|issues| issues.into_iter().map(Into::into).collect()).map_err(io::Error::other)
+	}
+}
+
+impl Publisher for GitHub {
+	fn plan(&self, mut plan: Plan) -> Result<Plan> {
+		let existing = self.existing_labels().map_err(Error::Plan)?;
+		plan.create_labels = plan.labels.iter().filter(|label| !existing.contains(label)).cloned().collect();
+		plan.steps = plan.create_labels.iter().map(|label| describe(&self.create_label(label))).collect();
+		plan.steps.push(describe(&self.create_issue(&plan)));
+		if let Some(issue_type) = &plan.metadata.issue_type {
+			plan.steps.push(describe(&self.set_issue_type("<number>", issue_type)));
+		}
+		Ok(plan)
+	}
+
+	fn execute(&self, plan: &Plan) -> Result<Issue> {
+		let mut rollback = Rollback::default();
+		for label in &plan.create_labels {
+			rollback.check(output(&mut self.create_label(label)), |e| Error::CreateLabel(label.clone(), e))?;
+			rollback.push(format!("label {label:?}"), || output(&mut self.delete_label(label)).map(drop));
+		}
+		let issue = Issue::from_url(rollback.check(output(&mut self.create_issue(plan)), Error::CreateIssue)?.trim().to_string(), Backend::Github);
+		if let Some(issue_type) = &plan.metadata.issue_type {
+			let url = issue.url.clone();
+			rollback.push(issue.url.clone(), move || output(&mut self.delete_issue(&url)).map(drop));
+			let number = issue.number.ok_or_else(|| io::Error::other("no issue number in the URL"));
+			rollback.check(number.and_then(|number| output(&mut self.set_issue_type(&number.to_string(), issue_type))), |e| Error::SetIssueType(issue.url.clone(), e))?;
+		}
+		Ok(issue)
+	}
+
+	fn fetch_issue(&self, issue: &Issue) -> io::Result<RemoteIssue> {
+		let issue = output(process::Command::new(&self.program).args(["issue", "view", &issue.url, "--json", ISSUE_FIELDS]))?;
+		serde_json::from_str::<GhIssue>(&issue).map(Into::into).map_err(io::Error::other)
+	}
+
+	fn comment(&self, issue: &Issue, body: &str) -> io::Result<()> {
+		output(process::Command::new(&self.program).args(["issue", "comment", &issue.url, "--body", body])).map(drop)
+	}
+
+	fn update_issue(&self, issue: &Issue, update: &Update) -> io::Result<()> {
+		if !update.add_labels.is_empty() {
+			let existing = self.existing_labels()?;
+			for label in update.add_labels.iter().filter(|label| !existing.contains(label)) {
+				output(&mut self.create_label(label))?;
+			}
+		}
+		let mut command = process::Command::new(&self.program);
+		command.args(["issue", "edit", &issue.url]);
+		if let Some(title) = &update.title {
+			command.args(["--title", title]);
+		}
+		for label in &update.add_labels {
+			command.args(["--add-label", label]);
+		}
+		for label in &update.remove_labels {
+			command.args(["--remove-label", label]);
+		}
+		output(&mut command).map(drop)
+	}
+}
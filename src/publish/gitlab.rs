@@ -0,0 +1,55 @@
+use super::{describe, output, Backend, Error, Issue, Plan, Publisher, Result};
+use crate::prelude::*;
+use std::{path::PathBuf, process};
+
+/// Publishes issues using the GitLab CLI.
+///
+/// GitLab creates missing labels on its own, so they are passed along as-is.
+/// Projects and issue types have no GitLab equivalent and are ignored.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GitLab {
+	pub program: PathBuf,
+}
+
+impl Default for GitLab {
+	fn default() -> Self {
+		Self { program: "glab".into() }
+	}
+}
+
+impl GitLab {
+	fn create_issue(&self, plan: &Plan) -> process::Command {
+		let mut command = process::Command::new(&self.program);
+		command.args(["issue", "create", "--yes"]).args(["--title", &plan.title]).args(["--description", &plan.body]);
+		if !plan.labels.is_empty() {
+			command.args(["--label", &plan.labels.join(",")]);
+		}
+		if !plan.metadata.assignees.is_empty() {
+			command.args(["--assignee", &plan.metadata.assignees.join(",")]);
+		}
+		if let Some(milestone) = &plan.metadata.milestone {
+			command.args(["--milestone", milestone]);
+		}
+		command
+	}
+}
+
+impl Publisher for GitLab {
+	fn plan(&self, mut plan: Plan) -> Result<Plan> {
+		plan.steps = vec![describe(&self.create_issue(&plan))];
+		Ok(plan)
+	}
+
+	// Creating the issue is the only step, so there is never anything to roll back.
+	fn execute(&self, plan: &Plan) -> Result<Issue> {
+		let stdout = output(&mut self.create_issue(plan)).map_err(Error::CreateIssue)?;
+		// glab reports progress before the URL of the new issue.
+		let url = stdout
+			.lines()
+			.rev()
+			.find(|line| line.starts_with("http"))
+			.ok_or_else(|| Error::CreateIssue(io::Error::other(format!("glab did not report an issue URL: {stdout}"))))?;
+		Ok(Issue::from_url(url.to_string(), Backend::Gitlab))
+	}
+}
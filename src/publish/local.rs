@@ -0,0 +1,57 @@
+use super::{Backend, Error, Issue, Plan, Publisher, Result};
+use crate::prelude::*;
+use std::path::PathBuf;
+
+/// Writes issues as markdown files into a directory, for trackers that live in the repository.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Local {
+	pub directory: PathBuf,
+}
+
+impl Default for Local {
+	fn default() -> Self {
+		Self { directory: "issues".into() }
+	}
+}
+
+impl Local {
+	/// Find an unused file name for an issue titled `title`.
+	fn path(&self, title: &str) -> io::Result<PathBuf> {
+		let slug = Tissue { title: title.into(), ..Default::default() }.slug();
+		let mut path = self.directory.join(format!("{slug}.md"));
+		let mut n = 1;
+		while path.try_exists()? {
+			n += 1;
+			path = self.directory.join(format!("{slug}-{n}.md"));
+		}
+		Ok(path)
+	}
+}
+
+impl Publisher for Local {
+	fn plan(&self, mut plan: Plan) -> Result<Plan> {
+		plan.steps = vec![format!("write {}", self.path(&plan.title).map_err(Error::Plan)?.display())];
+		Ok(plan)
+	}
+
+	fn execute(&self, plan: &Plan) -> Result<Issue> {
+		fs::create_dir_all(&self.directory).map_err(|e| Error::Write(self.directory.clone(), e))?;
+		let path = self.path(&plan.title).map_err(|e| Error::Write(self.directory.clone(), e))?;
+		let mut contents = format!("# {}\n", plan.title);
+		if !plan.labels.is_empty() || !plan.metadata.is_empty() {
+			contents += "\n";
+		}
+		if !plan.labels.is_empty() {
+			contents += &format!("Labels: {}\n", plan.labels.join(", "));
+		}
+		contents += &plan.metadata.to_string();
+		contents += &format!("\n{}", plan.body);
+		fs::write(&path, contents).map_err(|e| Error::Write(path.clone(), e))?;
+		Ok(Issue {
+			url: path.display().to_string(),
+			number: None,
+			backend: Backend::Local,
+		})
+	}
+}
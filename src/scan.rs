@@ -0,0 +1,193 @@
+//! Importing `TODO`-style comments from source code as tissues.
+
+use crate::prelude::*;
+use std::{
+	collections::BTreeSet,
+	path::{Path, PathBuf},
+	process,
+};
+
+const COMMENT_LEADERS: [&str; 5] = ["//", "/*", "#", "--", "<!--"];
+
+/// A marked comment found in the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+	pub marker: String,
+	pub text: String,
+	pub reference: Reference,
+}
+
+impl Comment {
+	/// The title a tissue created from this comment is given.
+	pub fn title(&self) -> String {
+		if self.text.is_empty() {
+			format!("{} in {}", self.marker, self.reference)
+		} else {
+			self.text.clone()
+		}
+	}
+
+	/// The tag a tissue created from this comment is given.
+	pub fn tag(&self) -> String {
+		self.marker.to_lowercase()
+	}
+
+	/// Whether `tissue` was created from this comment.
+	///
+	/// It must reference the same file, and either the same line of source or, for comments with text, have the same title.
+	/// Line numbers are left out, since they change whenever lines are added above the comment.
+	pub fn is_tracked_by(&self, tissue: &Tissue) -> bool {
+		let line = |reference: &Reference| reference.context.as_ref().map(|context| context.line.clone());
+		tissue
+			.references
+			.iter()
+			.any(|reference| reference.path == self.reference.path && ((line(reference).is_some() && line(reference) == line(&self.reference)) || (!self.text.is_empty() && tissue.title == self.text)))
+	}
+
+	pub fn to_tissue(&self) -> Tissue {
+		Tissue {
+			title: self.title(),
+			tags: BTreeSet::from([self.tag()]),
+			references: vec![self.reference.clone()],
+			..Default::default()
+		}
+	}
+}
+
+impl std::fmt::Display for Comment {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}: {} {}", self.reference, self.marker, self.text)
+	}
+}
+
+/// List the files under `paths` that git doesn't ignore.
+///
+/// An empty list of paths scans the whole working tree.
+pub fn files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+	let output = process::Command::new("git").args(["ls-files", "-z", "--cached", "--others", "--exclude-standard", "--"]).args(paths).output()?;
+	if output.status.success() {
+		Ok(output.stdout.split(|b| *b == 0).filter(|path| !path.is_empty()).map(|path| PathBuf::from(String::from_utf8_lossy(path).as_ref())).collect())
+	} else {
+		Err(io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()))
+	}
+}
+
+/// Find every comment beginning with one of `markers` in `files`.
+///
+/// Files that are missing or aren't text are skipped.
+pub fn comments(files: &[PathBuf], markers: &[String]) -> io::Result<Vec<Comment>> {
+	let mut comments = Vec::new();
+	for path in files {
+		let contents = match fs::read_to_string(path) {
+			Ok(contents) => contents,
+			Err(e) if matches!(e.kind(), io::ErrorKind::InvalidData | io::ErrorKind::NotFound) => continue,
+			Err(e) => return Err(e),
+		};
+		let lines = contents.lines().collect::<Vec<_>>();
+		for (line, text) in lines.iter().enumerate() {
+			if let Some((marker, text)) = parse(text, markers) {
+				comments.push(Comment {
+					marker: marker.into(),
+					text: text.into(),
+					reference: Reference {
+						path: path.clone(),
+						line: line + 1,
+						column: None,
+						context: Context::capture(&lines, line + 1),
+					},
+				});
+			}
+		}
+	}
+	Ok(comments)
+}
+
+/// Split a line such as `// TODO(evie): text` into its marker and text.
+///
+/// The marker must be the first word of a comment, so prose mentioning it is ignored.
+fn parse<'a>(line: &'a str, markers: &'a [String]) -> Option<(&'a str, &'a str)> {
+	let mut leaders = COMMENT_LEADERS.iter().flat_map(|leader| line.match_indices(leader)).collect::<Vec<_>>();
+	leaders.sort();
+	for (start, leader) in leaders {
+		// Leaders inside string literals don't start a comment.
+		if line[..start].matches('"').count() % 2 == 1 {
+			continue;
+		}
+		let comment = line[start + leader.len()..].trim_start().trim_start_matches(['/', '*', '!', '#', '-']).trim_start();
+		for marker in markers {
+			let Some(rest) = comment.strip_prefix(marker.as_str()) else {
+				continue;
+			};
+			let rest = match rest.strip_prefix('(') {
+				Some(rest) => match rest.split_once(')') {
+					Some((_, rest)) => rest,
+					None => continue,
+				},
+				None => rest,
+			};
+			if !rest.is_empty() && !rest.starts_with([':', ' ', '\t']) {
+				continue;
+			}
+			let text = rest.trim_start_matches(':').trim().trim_end_matches("-->").trim_end_matches("*/").trim_end();
+			return Some((marker, text));
+		}
+		// Anything after the first leader is part of this comment, even if it looks like another one.
+		break;
+	}
+	None
+}
+
+/// Whether `tissue` was created from a comment with one of `markers`.
+fn is_scanned(tissue: &Tissue, markers: &[String]) -> bool {
+	!tissue.references.is_empty() && markers.iter().any(|marker| tissue.tags.contains(&marker.to_lowercase()))
+}
+
+/// The open tissues, leaving out committed tissues kept for history.
+fn open(tissue_box: &TissueBox) -> impl Iterator<Item = (usize, &Tissue)> {
+	tissue_box.tissues.iter().enumerate().filter(|(_, tissue)| tissue.resolution.is_none())
+}
+
+/// Whether some tissue tracks `comment`: an open one, or a removed one that wasn't committed,
+/// so comments whose tissues were discarded or published aren't imported again.
+fn is_tracked(tissue_box: &TissueBox, comment: &Comment) -> bool {
+	let removed = tissue_box.recycle_bin.iter().filter(|tissue| !matches!(tissue.resolution, Some(Resolution::Committed { .. })));
+	open(tissue_box).map(|(_, tissue)| tissue).chain(removed).any(|tissue| comment.is_tracked_by(tissue))
+}
+
+/// Create a tissue for every comment that doesn't already have one, returning the comments that were added.
+pub fn import(tissue_box: &mut TissueBox, comments: &[Comment]) -> Vec<Comment> {
+	let mut added = Vec::new();
+	for comment in comments {
+		if is_tracked(tissue_box, comment) {
+			continue;
+		}
+		tissue_box.add(comment.to_tissue());
+		added.push(comment.clone());
+	}
+	added
+}
+
+/// Differences between the comments in the source code and the tissue box.
+#[derive(Debug, Default)]
+pub struct Check {
+	/// Comments no tissue was created for.
+	pub untracked: Vec<Comment>,
+	/// Indices of scanned tissues whose comment no longer exists.
+	pub stale: Vec<usize>,
+}
+
+/// Compare `comments` found under `paths` against the tissue box.
+///
+/// Only tissues referencing something under `paths` can be stale,
+/// so scanning part of the tree doesn't report tissues from the rest of it.
+pub fn check(tissue_box: &TissueBox, paths: &[PathBuf], comments: &[Comment], markers: &[String]) -> Check {
+	let scanned = |path: &Path| paths.is_empty() || paths.iter().any(|root| path.starts_with(root));
+	Check {
+		untracked: comments.iter().filter(|comment| !is_tracked(tissue_box, comment)).cloned().collect(),
+		stale: open(tissue_box)
+			.filter(|(_, tissue)| is_scanned(tissue, markers) && tissue.references.iter().all(|reference| scanned(&reference.path)))
+			.filter(|(_, tissue)| !comments.iter().any(|comment| comment.is_tracked_by(tissue)))
+			.map(|(index, _)| index)
+			.collect(),
+	}
+}
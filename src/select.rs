@@ -0,0 +1,75 @@
+//! Choosing several tissues at once, by index or by what they are.
+
+use crate::prelude::*;
+use std::ops::RangeInclusive;
+
+/// Indices typed as a list of single indices and inclusive ranges, such as `3`, `1-4` or `1-4,7`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Indices(pub Vec<RangeInclusive<usize>>);
+
+impl std::str::FromStr for Indices {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = || format!("expected indices such as 3, 1-4 or 1-4,7, found {s:?}");
+		let mut ranges = Vec::new();
+		for part in s.split(',') {
+			let (start, end) = part.split_once('-').unwrap_or((part, part));
+			let (start, end) = (start.trim().parse::<usize>().map_err(|_| error())?, end.trim().parse::<usize>().map_err(|_| error())?);
+			if start > end {
+				return Err(error());
+			}
+			ranges.push(start..=end);
+		}
+		Ok(Indices(ranges))
+	}
+}
+
+impl Indices {
+	/// Every index in order, once each, or the first index that isn't below `len`.
+	///
+	/// Ranges are checked before they're expanded, so a typo such as `0-99999999999` can't exhaust memory.
+	pub fn expand(&self, len: usize) -> Result<Vec<usize>, usize> {
+		if let Some(range) = self.0.iter().find(|range| *range.end() >= len) {
+			return Err((*range.start()).max(len));
+		}
+		let mut indices = self.0.iter().cloned().flatten().collect::<Vec<_>>();
+		indices.sort();
+		indices.dedup();
+		Ok(indices)
+	}
+}
+
+/// A condition on tissues typed as `key:value`, such as `tag:bug`, `status:review` or `priority:P1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Query {
+	Tag(String),
+	Status(String),
+	/// A priority, or none for tissues without one.
+	Priority(Option<Priority>),
+}
+
+impl std::str::FromStr for Query {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once(':') {
+			Some(("tag", tag)) => Ok(Query::Tag(tag.into())),
+			Some(("status", status)) => Ok(Query::Status(status.into())),
+			Some(("priority", "none")) => Ok(Query::Priority(None)),
+			Some(("priority", priority)) => priority.parse().map(|priority| Query::Priority(Some(priority))),
+			_ => Err(format!("expected a query such as tag:bug, status:review or priority:P1, found {s:?}")),
+		}
+	}
+}
+
+impl Query {
+	pub fn matches(&self, tissue_box: &TissueBox, index: usize) -> bool {
+		let tissue = &tissue_box.tissues[index];
+		match self {
+			Query::Tag(tag) => tissue.tags.contains(tag),
+			Query::Status(status) => tissue_box.status(index) == Some(status.as_str()),
+			Query::Priority(priority) => tissue.priority == *priority,
+		}
+	}
+}
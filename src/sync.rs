@@ -0,0 +1,152 @@
+//! Two-way syncing between tissues and the issues they are linked to.
+//!
+//! A linked tissue remembers what it and its issue agreed on at the last sync,
+//! so changes made on either side since then can be told apart and merged.
+
+use crate::{
+	prelude::*,
+	publish::{self, Publisher, Update},
+};
+
+/// What a linked tissue and its issue agreed on at the last sync.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Synced {
+	pub title: String,
+	/// The description lines the issue has seen, in its body or as comments.
+	pub descriptions: Vec<String>,
+	pub labels: Vec<String>,
+}
+
+impl Synced {
+	/// Record `tissue` as it is now, such as right after publishing it.
+	pub fn of(tissue: &Tissue) -> Self {
+		Self {
+			title: tissue.title.clone(),
+			descriptions: tissue.description.iter().map(|item| item.text.clone()).collect(),
+			labels: tissue.tags.iter().cloned().collect(),
+		}
+	}
+}
+
+/// The lines of `description` not in `seen`, matching each seen line once so that repeated lines still count.
+fn unseen(seen: &[String], description: &[Item]) -> Vec<Item> {
+	let mut seen = seen.iter().collect::<Vec<_>>();
+	description
+		.iter()
+		.filter(|item| match seen.iter().position(|line| **line == item.text) {
+			Some(position) => {
+				seen.swap_remove(position);
+				false
+			}
+			None => true,
+		})
+		.cloned()
+		.collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+	/// The issue was closed, so the tissue should be too.
+	Closed,
+	/// New description lines were posted to the issue as a comment.
+	Commented(usize),
+	/// The tissue took the title the issue was renamed to.
+	Renamed(String),
+	/// The issue took the title the tissue was renamed to.
+	RenamedIssue(String),
+	Tagged(String),
+	Untagged(String),
+	Labeled(String),
+	Unlabeled(String),
+	/// Both sides were renamed since the last sync, so neither was touched.
+	TitleConflict {
+		local: String,
+		remote: String,
+	},
+}
+
+impl std::fmt::Display for Change {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Change::Closed => write!(f, "closed, since the issue was"),
+			Change::Commented(1) => write!(f, "commented with 1 new description"),
+			Change::Commented(count) => write!(f, "commented with {count} new descriptions"),
+			Change::Renamed(title) => write!(f, "renamed to {title:?}"),
+			Change::RenamedIssue(title) => write!(f, "renamed the issue to {title:?}"),
+			Change::Tagged(tag) => write!(f, "tagged {tag:?}"),
+			Change::Untagged(tag) => write!(f, "untagged {tag:?}"),
+			Change::Labeled(label) => write!(f, "labeled the issue {label:?}"),
+			Change::Unlabeled(label) => write!(f, "unlabeled the issue {label:?}"),
+			Change::TitleConflict { local, remote } => write!(f, "conflict: renamed to {local:?} here but {remote:?} on the issue; edit either title to match the other"),
+		}
+	}
+}
+
+/// Sync `tissue` with the issue it is linked to, returning what changed.
+///
+/// Description lines added since the last sync are posted as a comment, labels are merged both ways,
+/// and a title changed on only one side is copied to the other.
+/// A closed issue is reported as [`Change::Closed`] but closing the tissue is left to the caller.
+pub fn sync(tissue: &mut Tissue, publisher: &dyn Publisher, style: publish::BodyStyle) -> io::Result<Vec<Change>> {
+	let Some(issue) = tissue.issue.clone() else {
+		return Ok(Vec::new());
+	};
+	// Tissues linked before syncing existed start from their local state.
+	let mut base = tissue.synced.clone().unwrap_or_else(|| Synced::of(tissue));
+	let remote = publisher.fetch_issue(&issue)?;
+	let mut changes = Vec::new();
+
+	let new = unseen(&base.descriptions, &tissue.description);
+	if !new.is_empty() {
+		publisher.comment(&issue, &publish::list(&new, style))?;
+		changes.push(Change::Commented(new.len()));
+	}
+	// Record the comment straight away so a failure below can't post it twice.
+	base.descriptions = Synced::of(tissue).descriptions;
+	tissue.synced = Some(base.clone());
+
+	let mut update = Update::default();
+	let mut title = base.title.clone();
+	let renamed = tissue.title != base.title;
+	let renamed_remotely = remote.title != base.title;
+	if renamed && renamed_remotely && tissue.title != remote.title {
+		changes.push(Change::TitleConflict {
+			local: tissue.title.clone(),
+			remote: remote.title.clone(),
+		});
+	} else if renamed_remotely {
+		title = remote.title.clone();
+		// Both sides may have been renamed to the same thing.
+		if !renamed {
+			tissue.title = title.clone();
+			changes.push(Change::Renamed(title.clone()));
+		}
+	} else if renamed {
+		title = tissue.title.clone();
+		update.title = Some(title.clone());
+		changes.push(Change::RenamedIssue(title.clone()));
+	}
+
+	update.add_labels = tissue.tags.iter().filter(|tag| !base.labels.contains(tag) && !remote.labels.contains(tag)).cloned().collect();
+	update.remove_labels = base.labels.iter().filter(|label| !tissue.tags.contains(*label) && remote.labels.contains(label)).cloned().collect();
+	let tagged = remote.labels.iter().filter(|label| !base.labels.contains(label) && !tissue.tags.contains(*label)).cloned().collect::<Vec<_>>();
+	let untagged = base.labels.iter().filter(|label| !remote.labels.contains(label) && tissue.tags.contains(*label)).cloned().collect::<Vec<_>>();
+
+	if !update.is_empty() {
+		publisher.update_issue(&issue, &update)?;
+	}
+	changes.extend(update.add_labels.into_iter().map(Change::Labeled));
+	changes.extend(update.remove_labels.into_iter().map(Change::Unlabeled));
+	for tag in &untagged {
+		tissue.tags.remove(tag);
+	}
+	tissue.tags.extend(tagged.iter().cloned());
+	changes.extend(tagged.into_iter().map(Change::Tagged));
+	changes.extend(untagged.into_iter().map(Change::Untagged));
+
+	if remote.closed {
+		changes.push(Change::Closed);
+	}
+	tissue.synced = Some(Synced { title, ..Synced::of(tissue) });
+	Ok(changes)
+}
@@ -0,0 +1,66 @@
+//! Reading dates and lengths of time typed on the command line.
+
+use chrono::{Datelike, Days, Local, NaiveDate, TimeDelta, Weekday};
+
+/// A length of time such as `90m`, `3d` or `1w`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Span(pub TimeDelta);
+
+impl std::str::FromStr for Span {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = || format!("expected a length of time such as 30m, 3d or 1w, found {s:?}");
+		let unit = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(error)?;
+		let count = s[..unit].parse::<i64>().map_err(|_| error())?;
+		match &s[unit..] {
+			"m" => TimeDelta::try_minutes(count),
+			"h" => TimeDelta::try_hours(count),
+			"d" => TimeDelta::try_days(count),
+			"w" => TimeDelta::try_weeks(count),
+			_ => None,
+		}
+		.map(Span)
+		.ok_or_else(error)
+	}
+}
+
+impl std::fmt::Display for Span {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let minutes = self.0.num_minutes();
+		match minutes {
+			_ if minutes >= 60 && minutes % 60 != 0 => write!(f, "{}h {}m", minutes / 60, minutes % 60),
+			_ if minutes >= 60 => write!(f, "{}h", minutes / 60),
+			_ => write!(f, "{minutes}m"),
+		}
+	}
+}
+
+/// The current date in the local time zone.
+pub fn today() -> NaiveDate {
+	Local::now().date_naive()
+}
+
+/// Read a date relative to `today`.
+///
+/// Accepts `today`, `tomorrow`, a weekday such as `fri` (always the next one, never today),
+/// a span of whole days from today such as `3d` or `2w`, or an ISO 8601 date such as `2026-11-01`.
+pub fn parse_date(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+	match s.to_lowercase().as_str() {
+		"today" => return Ok(today),
+		"tomorrow" => return Ok(today + Days::new(1)),
+		_ => {}
+	}
+	if let Ok(weekday) = s.parse::<Weekday>() {
+		let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+		return Ok(today + Days::new(if ahead == 0 { 7 } else { ahead.into() }));
+	}
+	// Spans such as `90m` would otherwise round down to today.
+	if let Ok(Span(span)) = s.parse() {
+		if span != TimeDelta::days(span.num_days()) {
+			return Err(format!("expected a whole number of days or weeks, found {s:?}"));
+		}
+		return today.checked_add_signed(span).ok_or_else(|| format!("{s:?} from today is too far ahead"));
+	}
+	NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| format!("expected a date such as tomorrow, fri, 3d or 2026-11-01, found {s:?}"))
+}
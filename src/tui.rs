@@ -1,13 +1,13 @@
 use crate::prelude::*;
 use crossterm::event::{self, KeyCode, KeyEventKind};
 use ratatui::{
-	layout::{Alignment, Rect},
-	style::Stylize,
+	layout::{Alignment, Constraint, Layout, Rect},
+	style::{Color, Modifier, Style, Stylize},
 	symbols::border,
 	text::{Line, Span, Text},
 	widgets::{
 		block::{Position, Title},
-		Block, Padding, Paragraph,
+		Block, Clear, Padding, Paragraph, Wrap,
 	},
 	DefaultTerminal,
 };
@@ -15,6 +15,7 @@ use std::{
 	io::{self, Write},
 	path::Path,
 	process,
+	time::Duration,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -24,23 +25,55 @@ enum Error {
 	#[error("clipboard is not available")]
 	MissingClipboard,
 	#[error(transparent)]
+	Publish(#[from] publish::Error),
+	#[error(transparent)]
+	Status(#[from] workflow::Error),
+	#[error("{0}")]
+	InvalidTag(String),
+	#[error("no tags to remove")]
+	NoTags,
+	#[error("already committed")]
+	AlreadyCommitted,
+	#[error("{0} unchecked items; press f to commit anyway")]
+	UncheckedItems(usize),
+	#[error("already published as {0}")]
+	AlreadyPublished(String),
+	#[error(transparent)]
 	Arboard(#[from] arboard::Error),
 }
 
 enum Mode {
 	Normal,
 	Help,
+	/// Extending a range of tissues to mark from the one at this index.
+	Visual(usize),
 	Add(String),
 	Describe(String),
-	Tag(String),
+	/// Typing a tag, with the completion selected in the popup.
+	Tag(String, usize),
 	Edit(String),
 	Copy,
-	Publish,
-	Commit,
+	/// Confirming publishing each of these tissues, by index.
+	Publish(Vec<(usize, publish::Plan)>),
+	/// Confirming a commit, warning about the open tissues still blocking it.
+	Commit(Vec<usize>),
 	Remove,
 	RemoveDescription(usize),
-	RemoveTag(String),
+	Check(usize),
+	/// Picking which of the targeted tissues' tags to remove.
+	RemoveTag(usize),
 	Restore(usize),
+	OpenReference(usize),
+	OpenAttachment(usize),
+}
+
+/// Which part of the selected tissue is highlighted.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Cursor {
+	Title,
+	Description(usize),
+	Reference(usize),
+	Attachment(usize),
 }
 
 pub fn run(path: &Path, clipboard_daemon: Option<&Path>) -> io::Result<()> {
@@ -93,6 +126,7 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 		false
 	};
 	let mut tissue_box = TissueBox::open(path)?;
+	let mut current_branch = crate::current_branch();
 	if init_git_exclude {
 		let mut git_exclude = fs::OpenOptions::new().append(true).open(".git/info/exclude")?;
 		git_exclude.write_all("\n# Created by tissuebox\n".as_bytes())?;
@@ -101,6 +135,12 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 	}
 
 	let mut index = 0;
+	// IDs of tissues whose subtasks are hidden.
+	let mut collapsed = HashSet::new();
+	// IDs of tissues marked to act on together, instead of just the selected one.
+	let mut marked = HashSet::new();
+	// Whether the selected tissue's notes are shown beside the list.
+	let mut detail = false;
 	let mut mode = Mode::Normal;
 	let mut last_error: Result<(), Error> = Ok(());
 	loop {
@@ -123,29 +163,122 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 			// TissueBox
 			let title = Title::from(" tissuebox ".red().bold());
 			let instructions = instructions(&mode);
-			let block = Block::bordered()
+			let mut block = Block::bordered()
 				.title(title.alignment(Alignment::Center))
 				.title(instructions.alignment(Alignment::Center).position(Position::Bottom))
 				.padding(Padding::horizontal(2))
 				.border_set(border::ROUNDED);
+			if !marked.is_empty() {
+				block = block.title(Title::from(format!(" {} marked ", marked.len()).blue().bold()).alignment(Alignment::Left));
+			}
+			if let Some(running) = tissue_box.running() {
+				block = block.title(Title::from(timer(&tissue_box.tissues[running], tissue_box.starred == Some(running))).alignment(Alignment::Right));
+			}
 
 			let mut body = Text::default();
 			match &mode {
 				Mode::Help => {
 					help(&mut body);
 				}
-				Mode::Restore(index) => {
-					format_tissues(&mut body, &tissue_box.recycle_bin, *index, None, None);
+				Mode::Publish(plans) => {
+					for (i, (_, plan)) in plans.iter().enumerate() {
+						if i > 0 {
+							body.lines.push(Line::default());
+						}
+						preview(&mut body, plan);
+					}
 				}
-				Mode::RemoveDescription(description_index) => {
-					format_tissues(&mut body, &tissue_box.tissues, index, tissue_box.starred, Some(*description_index));
+				Mode::Restore(index) => {
+					let view = View {
+						index: *index,
+						starred: None,
+						cursor: Cursor::Title,
+						checked_out: None,
+						collapsed: &HashSet::new(),
+						marked: &HashSet::new(),
+						statuses: &[],
+						status_width: 0,
+						too_big: None,
+						tags: &tissue_box.tags,
+						progress: &|i| tissue_box.recycle_bin[i].progress(),
+						blocked: &|_| false,
+					};
+					format_tissues(&mut body, &tissue_box.recycle_bin, view);
 				}
 				_ => {
-					format_tissues(&mut body, &tissue_box.tissues, index, tissue_box.starred, None);
+					let cursor = match mode {
+						Mode::RemoveDescription(description_index) | Mode::Check(description_index) => Cursor::Description(description_index),
+						Mode::OpenReference(reference_index) => Cursor::Reference(reference_index),
+						Mode::OpenAttachment(attachment_index) => Cursor::Attachment(attachment_index),
+						_ => Cursor::Title,
+					};
+					let status_width = tissue_box.status_width();
+					let statuses = match status_width {
+						Some(_) => (0..tissue_box.tissues.len()).map(|i| tissue_box.status(i)).collect(),
+						None => Vec::new(),
+					};
+					// The range being marked is shown as marked already.
+					let mut shown_marked = marked.clone();
+					if let Mode::Visual(anchor) = mode {
+						shown_marked.extend(visual_range(&tissue_box, anchor, index, &collapsed));
+					}
+					let view = View {
+						index,
+						starred: tissue_box.starred,
+						cursor,
+						checked_out: current_branch.as_deref().and_then(|branch| tissue_box.branch_owner(branch)),
+						collapsed: &collapsed,
+						marked: &shown_marked,
+						statuses: &statuses,
+						status_width: status_width.unwrap_or(0),
+						too_big: Some(&tissue_box.config.too_big),
+						tags: &tissue_box.tags,
+						progress: &|i| tissue_box.progress(i),
+						blocked: &|i| !tissue_box.blockers(i).is_empty(),
+					};
+					format_tissues(&mut body, &tissue_box.tissues, view);
 				}
 			}
-			let paragraph_area = Rect { y: area.y + 4, height: area.height - 5, ..area };
-			frame.render_widget(Paragraph::new(body).block(block).scroll(((sum_lines(&tissue_box.tissues, index) as u16).saturating_sub(paragraph_area.height / 2 - 1), 0)), paragraph_area);
+			let mut paragraph_area = Rect { y: area.y + 4, height: area.height - 5, ..area };
+			if detail && !tissue_box.tissues.is_empty() && !matches!(mode, Mode::Help | Mode::Publish(_) | Mode::Restore(_)) {
+				let [list_area, detail_area] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(paragraph_area);
+				paragraph_area = list_area;
+				let tissue = &tissue_box.tissues[index];
+				let notes = if tissue.notes.is_empty() { Text::from(" No notes yet; press n to write some".dark_gray()) } else { markdown(&tissue.notes) };
+				let block = Block::bordered()
+					.title(Title::from(format!(" {} ", tissue.title).bold()).alignment(Alignment::Center))
+					.padding(Padding::horizontal(1))
+					.border_set(border::ROUNDED);
+				frame.render_widget(Paragraph::new(notes).block(block).wrap(Wrap { trim: false }), detail_area);
+			}
+			let scroll = match mode {
+				Mode::Help | Mode::Publish(_) => 0,
+				_ => (sum_lines(&tissue_box.tissues, index, &collapsed) as u16).saturating_sub(paragraph_area.height / 2 - 1),
+			};
+			frame.render_widget(Paragraph::new(body).block(block).scroll((scroll, 0)), paragraph_area);
+
+			// Tag popup
+			let popup = match &mode {
+				Mode::Tag(tag, selected) => Some((tag_completions(&tissue_box, index, tag), *selected)),
+				Mode::RemoveTag(selected) => Some((target_tags(&tissue_box, index, &marked), *selected)),
+				_ => None,
+			};
+			if let Some((tags, selected)) = popup.filter(|(tags, _)| !tags.is_empty()) {
+				let shown = tags.len().min(POPUP_TAGS);
+				// Scroll so the selected tag is always shown.
+				let skip = (selected + 1).saturating_sub(shown);
+				let lines = tags.iter().enumerate().skip(skip).take(shown).map(|(i, tag)| if i == selected { Line::from(tag.black().on_white()) } else { Line::from(tag.magenta()) });
+				let width = (tags.iter().map(|tag| tag.len()).max().unwrap_or(0) as u16 + 4).min(area.width.saturating_sub(4));
+				let height = shown as u16 + 2;
+				let popup_area = Rect {
+					x: area.x + 3,
+					y: (area.y + area.height).saturating_sub(height + 2),
+					width,
+					height,
+				};
+				frame.render_widget(Clear, popup_area);
+				frame.render_widget(Paragraph::new(lines.collect::<Vec<_>>()).block(Block::bordered().border_set(border::ROUNDED).padding(Padding::horizontal(1))), popup_area);
+			}
 
 			// Errors
 			if let Err(msg) = &last_error {
@@ -153,15 +286,25 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 			}
 		})?;
 
+		// Redraw every second while a timer is running to keep it live.
+		if tissue_box.running().is_some() && !event::poll(Duration::from_secs(1))? {
+			continue;
+		}
 		if let event::Event::Key(key) = event::read()? {
 			if key.kind == KeyEventKind::Press {
 				if key.code == KeyCode::Esc {
+					// Escaping when there's nothing else to escape clears the marks.
+					if let Mode::Normal = mode {
+						marked.clear();
+					}
 					mode = Mode::Normal;
 				}
 				if let (Mode::Normal, KeyCode::Char('q')) = (&mode, key.code) {
 					return Ok(());
+				} else if let (Mode::Normal, KeyCode::Enter) = (&mode, key.code) {
+					detail = !detail;
 				} else {
-					mode = match input(mode, key.code, &mut index, &mut tissue_box) {
+					mode = match input(mode, key.code, &mut index, &mut tissue_box, &mut collapsed, &mut marked) {
 						InputResult::Mode(mode) => mode,
 						InputResult::Copy(text) => {
 							if let Some(clipboard_daemon) = clipboard_daemon {
@@ -184,10 +327,36 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 							last_error = error;
 							Mode::Normal
 						}
+						InputResult::Open(reference) => {
+							// Save first, so the reference stays re-anchored even if the editor fails.
+							let saved = tissue_box.save(path);
+							ratatui::restore();
+							let result = reference.open();
+							terminal = ratatui::init();
+							terminal.clear()?;
+							last_error = saved.and(result).map_err(Error::from);
+							Mode::Normal
+						}
+						InputResult::EditNotes => {
+							ratatui::restore();
+							let result = tissue_box.tissues[index].edit_notes();
+							terminal = ratatui::init();
+							terminal.clear()?;
+							last_error = result.and_then(|()| tissue_box.save(path)).map_err(Error::from);
+							Mode::Normal
+						}
 						InputResult::Changed => {
+							current_branch = crate::current_branch();
+							marked.clear();
 							last_error = tissue_box.save(path).map_err(Error::from);
 							Mode::Normal
 						}
+						InputResult::PartlyChanged(error) => {
+							current_branch = crate::current_branch();
+							marked.clear();
+							last_error = tissue_box.save(path).map_err(Error::from).and(Err(error));
+							Mode::Normal
+						}
 					}
 				}
 			}
@@ -198,8 +367,12 @@ fn tui(mut terminal: DefaultTerminal, path: &Path, clipboard_daemon: Option<&Pat
 enum InputResult {
 	Mode(Mode),
 	Copy(String),
+	Open(Reference),
+	EditNotes,
 	Error(Result<(), Error>),
 	Changed,
+	/// Some changes were made before failing, and must still be saved.
+	PartlyChanged(Error),
 }
 
 impl From<Mode> for InputResult {
@@ -220,7 +393,7 @@ impl<T: Into<Error>> From<Result<(), T>> for InputResult {
 	}
 }
 
-fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBox) -> InputResult {
+fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBox, collapsed: &mut HashSet<u64>, marked: &mut HashSet<u64>) -> InputResult {
 	fn gather_line(line: &mut String, code: KeyCode) -> bool {
 		match code {
 			KeyCode::Backspace => {
@@ -236,11 +409,27 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 	match mode {
 		Mode::Normal => match code {
 			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => {
-				*index = index.saturating_sub(1);
+				*index = previous(tissue_box, *index, collapsed);
 				Mode::Normal.into()
 			}
 			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => {
-				*index += 1;
+				*index = next(tissue_box, *index, collapsed);
+				Mode::Normal.into()
+			}
+			KeyCode::Char(' ') if !tissue_box.tissues.is_empty() => {
+				let id = tissue_box.tissues[*index].id;
+				if !marked.remove(&id) {
+					marked.insert(id);
+				}
+				*index = next(tissue_box, *index, collapsed);
+				Mode::Normal.into()
+			}
+			KeyCode::Char('v') if !tissue_box.tissues.is_empty() => Mode::Visual(*index).into(),
+			KeyCode::Char('z') if !tissue_box.tissues.is_empty() => {
+				let id = tissue_box.tissues[*index].id;
+				if !collapsed.remove(&id) && tissue_box.tissues.iter().any(|tissue| tissue.parent == Some(id)) {
+					collapsed.insert(id);
+				}
 				Mode::Normal.into()
 			}
 			KeyCode::Char('H') => Mode::Help.into(),
@@ -253,12 +442,64 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 				}
 			}
 			KeyCode::Char('d') if !tissue_box.tissues.is_empty() => Mode::Describe(String::new()).into(),
-			KeyCode::Char('t') if !tissue_box.tissues.is_empty() => Mode::Tag(String::new()).into(),
+			KeyCode::Char('t') if !tissue_box.tissues.is_empty() => Mode::Tag(String::new(), 0).into(),
 			KeyCode::Char('e') if !tissue_box.tissues.is_empty() => Mode::Edit(String::new()).into(),
 			KeyCode::Char('c') if !tissue_box.tissues.is_empty() => Mode::Copy.into(),
-			KeyCode::Char('C') if !tissue_box.tissues.is_empty() => Mode::Commit.into(),
-			KeyCode::Char('P') if !tissue_box.tissues.is_empty() => Mode::Publish.into(),
+			KeyCode::Char('C') if !tissue_box.tissues.is_empty() => match tissue_box.tissues[*index].resolution {
+				Some(_) => Error::AlreadyCommitted.into(),
+				None => Mode::Commit(tissue_box.blockers(*index)).into(),
+			},
+			KeyCode::Char('P') if !tissue_box.tissues.is_empty() => {
+				let targets = targets(tissue_box, *index, marked);
+				if let Some(issue) = targets.iter().find_map(|&i| tissue_box.tissues[i].issue.as_ref()) {
+					return Error::AlreadyPublished(issue.url.clone()).into();
+				}
+				match targets.into_iter().map(|i| Ok((i, tissue_box.config.publish.plan(&tissue_box.tissues[i], None)?))).collect::<publish::Result<Vec<_>>>() {
+					Ok(plans) => Mode::Publish(plans).into(),
+					Err(msg) => msg.into(),
+				}
+			}
 			KeyCode::Char('r') if !tissue_box.tissues.is_empty() => Mode::Remove.into(),
+			KeyCode::Char('n') if !tissue_box.tissues.is_empty() => InputResult::EditNotes,
+			KeyCode::Char('x') if !tissue_box.tissues.is_empty() => match tissue_box.tissues[*index].description.len() {
+				0 => Mode::Normal.into(),
+				1 => toggle_item(tissue_box, *index, 0),
+				_ => Mode::Check(0).into(),
+			},
+			KeyCode::Char('o') if !tissue_box.tissues.is_empty() => match tissue_box.tissues[*index].references.len() {
+				0 => Mode::Normal.into(),
+				1 => open_reference(tissue_box, *index, 0),
+				_ => Mode::OpenReference(0).into(),
+			},
+			KeyCode::Char('O') if !tissue_box.tissues.is_empty() => match tissue_box.tissues[*index].attachments.len() {
+				0 => Mode::Normal.into(),
+				1 => open_attachment(tissue_box, *index, 0),
+				_ => Mode::OpenAttachment(0).into(),
+			},
+			KeyCode::Char('+') if !tissue_box.tissues.is_empty() => {
+				let tissue = &mut tissue_box.tissues[*index];
+				tissue.priority = Priority::raise(tissue.priority);
+				InputResult::Changed
+			}
+			KeyCode::Char('-') if !tissue_box.tissues.is_empty() => {
+				let tissue = &mut tissue_box.tissues[*index];
+				tissue.priority = Priority::lower(tissue.priority);
+				InputResult::Changed
+			}
+			// Marked tissues all move to the status after the selected one's.
+			KeyCode::Char(c @ ('s' | 'S')) if !tissue_box.tissues.is_empty() => match tissue_box.config.workflow.cycle(tissue_box.status(*index), c == 'S').map(String::from) {
+				Some(status) => {
+					let targets = targets(tissue_box, *index, marked);
+					if let Err(msg) = targets.iter().try_for_each(|&i| tissue_box.config.workflow.check(tissue_box.status(i), &status)) {
+						return msg.into();
+					}
+					for i in targets {
+						tissue_box.set_status(i, &status).expect("checked above");
+					}
+					InputResult::Changed
+				}
+				None => Mode::Normal.into(),
+			},
 			KeyCode::Char('*') if !tissue_box.tissues.is_empty() => {
 				if let Some(starred) = tissue_box.starred {
 					if starred == *index {
@@ -273,6 +514,21 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 			}
 			_ => Mode::Normal.into(),
 		},
+		Mode::Visual(anchor) => match code {
+			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => {
+				*index = previous(tissue_box, *index, collapsed);
+				Mode::Visual(anchor).into()
+			}
+			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => {
+				*index = next(tissue_box, *index, collapsed);
+				Mode::Visual(anchor).into()
+			}
+			KeyCode::Char('v') | KeyCode::Char(' ') | KeyCode::Enter => {
+				marked.extend(visual_range(tissue_box, anchor, *index, collapsed));
+				Mode::Normal.into()
+			}
+			_ => Mode::Visual(anchor).into(),
+		},
 		m @ Mode::Help => {
 			if let KeyCode::Char(_) = code {
 				Mode::Normal.into()
@@ -296,14 +552,27 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 				Mode::Describe(description).into()
 			}
 		}
-		Mode::Tag(mut tag) => {
-			if gather_line(&mut tag, code) {
-				tissue_box.tissues[*index].tag(tag);
+		Mode::Tag(mut tag, selected) => match code {
+			KeyCode::Up => Mode::Tag(tag, selected.saturating_sub(1)).into(),
+			KeyCode::Down => {
+				let count = tag_completions(tissue_box, *index, &tag).len();
+				Mode::Tag(tag, (selected + 1).min(count.saturating_sub(1))).into()
+			}
+			KeyCode::Tab => {
+				let completion = tag_completions(tissue_box, *index, &tag).get(selected).map(|completion| completion.to_string());
+				Mode::Tag(completion.unwrap_or(tag), 0).into()
+			}
+			_ if gather_line(&mut tag, code) => {
+				if let Err(msg) = tissue_box.check_new_tag(&tag, &[]) {
+					return Error::InvalidTag(msg).into();
+				}
+				for i in targets(tissue_box, *index, marked) {
+					tissue_box.tissues[i].tag(tag.clone());
+				}
 				InputResult::Changed
-			} else {
-				Mode::Tag(tag).into()
 			}
-		}
+			_ => Mode::Tag(tag, 0).into(),
+		},
 		Mode::Edit(mut title) => {
 			if gather_line(&mut title, code) {
 				tissue_box.tissues[*index].title = title;
@@ -314,41 +583,34 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 		}
 		Mode::Copy => match code {
 			KeyCode::Char('t') => InputResult::Copy(tissue_box.tissues[*index].title.clone()),
-			KeyCode::Char('d') => InputResult::Copy(tissue_box.tissues[*index].description.join("\n")),
+			KeyCode::Char('d') => InputResult::Copy(tissue_box.tissues[*index].description.iter().map(|item| item.text.as_str()).collect::<Vec<_>>().join("\n")),
 			KeyCode::Char('l') => InputResult::Copy(tissue_box.to_string()),
 			_ => Mode::Copy.into(),
 		},
-		Mode::Publish => match code {
-			KeyCode::Char('y') | KeyCode::Char('Y') => {
-				let tissue = &tissue_box.tissues[*index];
-				match tissue.publish() {
-					Ok(()) => {
-						let _ = tissue_box.remove(*index);
-						InputResult::Changed
-					}
-					Err(msg) => msg.into(),
-				}
-			}
+		Mode::Publish(plans) => match code {
+			KeyCode::Char('y') | KeyCode::Char('Y') => match tissue_box.publish_all(&plans, None) {
+				(_, Ok(())) => InputResult::Changed,
+				(issues, Err(msg)) if issues.is_empty() => msg.into(),
+				(_, Err(msg)) => InputResult::PartlyChanged(msg.into()),
+			},
 			KeyCode::Char('n') | KeyCode::Char('N') => Mode::Normal.into(),
-			_ => Mode::Publish.into(),
+			_ => Mode::Publish(plans).into(),
 		},
-		Mode::Commit => match code {
-			KeyCode::Char('y') | KeyCode::Char('Y') => {
-				let tissue = &tissue_box.tissues[*index];
-				match tissue.commit() {
-					Ok(()) => {
-						let _ = tissue_box.remove(*index);
-						InputResult::Changed
-					}
-					Err(msg) => msg.into(),
+		Mode::Commit(blockers) => match code {
+			KeyCode::Char(c @ ('y' | 'Y' | 'f')) => match tissue_box.tissues[*index].commit(&tissue_box.config, c == 'f') {
+				Ok(hash) => {
+					let _ = tissue_box.close_committed(*index, hash);
+					InputResult::Changed
 				}
-			}
+				Err(CommitError::UncheckedItems(count)) => Error::UncheckedItems(count).into(),
+				Err(CommitError::Git(msg)) => msg.into(),
+			},
 			KeyCode::Char('n') | KeyCode::Char('N') => Mode::Normal.into(),
-			_ => Mode::Commit.into(),
+			_ => Mode::Commit(blockers).into(),
 		},
 		Mode::Remove => match code {
 			KeyCode::Char('T') => {
-				let _ = tissue_box.remove(*index);
+				tissue_box.remove_all(&targets(tissue_box, *index, marked));
 				InputResult::Changed
 			}
 			KeyCode::Char('d') => {
@@ -358,7 +620,8 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 					Mode::RemoveDescription(0).into()
 				}
 			}
-			KeyCode::Char('t') => Mode::RemoveTag(String::new()).into(),
+			KeyCode::Char('t') if target_tags(tissue_box, *index, marked).is_empty() => Error::NoTags.into(),
+			KeyCode::Char('t') => Mode::RemoveTag(0).into(),
 			_ => Mode::Remove.into(),
 		},
 		Mode::RemoveDescription(i) => {
@@ -373,14 +636,42 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 				_ => Mode::RemoveDescription(i).into(),
 			}
 		}
-		Mode::RemoveTag(mut tag) => {
-			if gather_line(&mut tag, code) {
-				tissue_box.tissues[*index].tags.remove(&tag);
-				InputResult::Changed
-			} else {
-				Mode::RemoveTag(tag).into()
+		Mode::Check(i) => match code {
+			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => Mode::Check(i.saturating_sub(1)).into(),
+			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => Mode::Check((i + 1).min(tissue_box.tissues[*index].description.len() - 1)).into(),
+			KeyCode::Enter | KeyCode::Char('x') | KeyCode::Char(' ') => toggle_item(tissue_box, *index, i),
+			_ => Mode::Check(i).into(),
+		},
+		Mode::RemoveTag(i) => {
+			let tags = target_tags(tissue_box, *index, marked);
+			match code {
+				KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => Mode::RemoveTag(i.saturating_sub(1)).into(),
+				KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => Mode::RemoveTag((i + 1).min(tags.len().saturating_sub(1))).into(),
+				KeyCode::Enter => match tags.get(i) {
+					Some(tag) => {
+						let tag = tag.to_string();
+						for i in targets(tissue_box, *index, marked) {
+							tissue_box.tissues[i].tags.remove(&tag);
+						}
+						InputResult::Changed
+					}
+					None => Error::NoTags.into(),
+				},
+				_ => Mode::RemoveTag(i).into(),
 			}
 		}
+		Mode::OpenReference(i) => match code {
+			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => Mode::OpenReference(i.saturating_sub(1)).into(),
+			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => Mode::OpenReference((i + 1).min(tissue_box.tissues[*index].references.len() - 1)).into(),
+			KeyCode::Enter => open_reference(tissue_box, *index, i),
+			_ => Mode::OpenReference(i).into(),
+		},
+		Mode::OpenAttachment(i) => match code {
+			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => Mode::OpenAttachment(i.saturating_sub(1)).into(),
+			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => Mode::OpenAttachment((i + 1).min(tissue_box.tissues[*index].attachments.len() - 1)).into(),
+			KeyCode::Enter => open_attachment(tissue_box, *index, i),
+			_ => Mode::OpenAttachment(i).into(),
+		},
 		Mode::Restore(index) => match code {
 			KeyCode::Char('k') | KeyCode::Char('h') | KeyCode::Up | KeyCode::Left => Mode::Restore(index.saturating_sub(1)).into(),
 			KeyCode::Char('j') | KeyCode::Char('l') | KeyCode::Down | KeyCode::Right => Mode::Restore((index + 1).min(tissue_box.recycle_bin.len() - 1)).into(),
@@ -393,39 +684,271 @@ fn input(mode: Mode, code: KeyCode, index: &mut usize, tissue_box: &mut TissueBo
 	}
 }
 
-fn sum_lines(tissues: &[Tissue], index: usize) -> usize {
-	tissues.iter().take(index).fold(0, |a, b| a + 1 + b.description.len())
+fn toggle_item(tissue_box: &mut TissueBox, index: usize, item: usize) -> InputResult {
+	let item = &mut tissue_box.tissues[index].description[item];
+	item.done = !item.done;
+	InputResult::Changed
 }
 
-fn format_tissues(body: &mut Text, tissues: &[Tissue], index: usize, starred: Option<usize>, description_index: Option<usize>) {
+/// Re-anchor a reference before handing it off to be opened, since it may have moved while the tissue sat in the box.
+fn open_reference(tissue_box: &mut TissueBox, index: usize, reference: usize) -> InputResult {
+	let reference = &mut tissue_box.tissues[index].references[reference];
+	match reference.reanchor() {
+		Ok(_) => InputResult::Open(reference.clone()),
+		Err(msg) => msg.into(),
+	}
+}
+
+fn open_attachment(tissue_box: &TissueBox, index: usize, attachment: usize) -> InputResult {
+	let tissue = &tissue_box.tissues[index];
+	match tissue_box.attachment_target(tissue, &tissue.attachments[attachment]) {
+		Some(target) => open_externally(&target).into(),
+		None => Mode::Normal.into(),
+	}
+}
+
+/// How many tags the tag popup shows at once.
+const POPUP_TAGS: usize = 8;
+
+/// Tags in use elsewhere that the tissue at `index` doesn't have yet, fuzzily matching `query`, best first.
+pub fn tag_completions<'a>(tissue_box: &'a TissueBox, index: usize, query: &str) -> Vec<&'a str> {
+	let tags = &tissue_box.tissues[index].tags;
+	let mut matches = tissue_box.tag_counts().into_keys().filter(|tag| !tags.contains(*tag)).filter_map(|tag| Some((fuzzy_score(query, tag)?, tag))).collect::<Vec<_>>();
+	matches.sort();
+	matches.into_iter().map(|(_, tag)| tag).collect()
+}
+
+/// How well `candidate` matches `query` when its characters appear in order, ignoring case; lower is better.
+///
+/// Prefix matches come first, then matches whose characters are closest together.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<(bool, usize)> {
+	let candidate = candidate.to_lowercase();
+	let mut positions = Vec::new();
+	let mut rest = candidate.char_indices();
+	for c in query.to_lowercase().chars() {
+		positions.push(rest.find(|&(_, d)| d == c)?.0);
+	}
+	match (positions.first(), positions.last()) {
+		(Some(&first), Some(&last)) => Some((first != 0, last - first)),
+		_ => Some((false, 0)),
+	}
+}
+
+/// The tags of every targeted tissue, in order.
+fn target_tags<'a>(tissue_box: &'a TissueBox, index: usize, marked: &HashSet<u64>) -> Vec<&'a str> {
+	targets(tissue_box, index, marked).into_iter().flat_map(|i| &tissue_box.tissues[i].tags).map(String::as_str).collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+/// The indices of the marked tissues in order, or just the selected one if none are marked.
+fn targets(tissue_box: &TissueBox, index: usize, marked: &HashSet<u64>) -> Vec<usize> {
+	if marked.is_empty() {
+		vec![index]
+	} else {
+		(0..tissue_box.tissues.len()).filter(|&i| marked.contains(&tissue_box.tissues[i].id)).collect()
+	}
+}
+
+/// IDs of the shown tissues from `anchor` to `index`, in either direction.
+fn visual_range(tissue_box: &TissueBox, anchor: usize, index: usize, collapsed: &HashSet<u64>) -> Vec<u64> {
+	(anchor.min(index)..=anchor.max(index)).filter(|&i| !is_hidden(&tissue_box.tissues, i, collapsed)).map(|i| tissue_box.tissues[i].id).collect()
+}
+
+/// The index of the shown tissue before `index`, or `index` if there isn't one.
+fn previous(tissue_box: &TissueBox, index: usize, collapsed: &HashSet<u64>) -> usize {
+	(0..index).rev().find(|&i| !is_hidden(&tissue_box.tissues, i, collapsed)).unwrap_or(index)
+}
+
+/// The index of the shown tissue after `index`, or `index` if there isn't one.
+fn next(tissue_box: &TissueBox, index: usize, collapsed: &HashSet<u64>) -> usize {
+	(index + 1..tissue_box.tissues.len()).find(|&i| !is_hidden(&tissue_box.tissues, i, collapsed)).unwrap_or(index)
+}
+
+/// Whether the tissue at `index` is hidden under a collapsed ancestor.
+fn is_hidden(tissues: &[Tissue], index: usize, collapsed: &HashSet<u64>) -> bool {
+	ancestors(tissues, index).iter().any(|&ancestor| collapsed.contains(&tissues[ancestor].id))
+}
+
+fn sum_lines(tissues: &[Tissue], index: usize, collapsed: &HashSet<u64>) -> usize {
+	(0..index)
+		.filter(|&i| !is_hidden(tissues, i, collapsed))
+		.map(|i| 1 + tissues[i].description.len() + tissues[i].references.len() + tissues[i].attachments.len())
+		.sum()
+}
+
+/// How tissues are drawn, besides the tissues themselves.
+struct View<'a> {
+	index: usize,
+	starred: Option<usize>,
+	cursor: Cursor,
+	/// The tissue whose branch is checked out.
+	checked_out: Option<usize>,
+	collapsed: &'a HashSet<u64>,
+	/// IDs of tissues marked to act on together.
+	marked: &'a HashSet<u64>,
+	/// The status of each tissue, or nothing to leave statuses out.
+	statuses: &'a [Option<&'a str>],
+	/// How wide the column of statuses is.
+	status_width: usize,
+	/// Limits past which tissues are flagged for publishing, or nothing to flag none.
+	too_big: Option<&'a TooBig>,
+	/// Descriptions and colors of tags, by name.
+	tags: &'a BTreeMap<String, TagInfo>,
+	/// How many items and subtasks of a tissue are done, and how many there are.
+	progress: &'a dyn Fn(usize) -> (usize, usize),
+	/// Whether a tissue is waiting on another.
+	blocked: &'a dyn Fn(usize) -> bool,
+}
+
+/// Tissues due within this many days are highlighted.
+const DUE_SOON_DAYS: i64 = 3;
+
+fn format_tissues(body: &mut Text, tissues: &[Tissue], view: View) {
+	let View {
+		index,
+		starred,
+		cursor,
+		checked_out,
+		collapsed,
+		marked,
+		statuses,
+		status_width,
+		too_big,
+		tags,
+		progress,
+		blocked,
+	} = view;
+	let now = chrono::Utc::now();
+	let today = crate::time::today();
+	let due_soon = crate::time::Span(chrono::TimeDelta::days(DUE_SOON_DAYS));
 	for (i, tissue) in tissues.iter().enumerate() {
-		let mut title = Span::default();
+		if is_hidden(tissues, i, collapsed) {
+			continue;
+		}
+		let indent = "  ".repeat(ancestors(tissues, i).len());
+		// Resolved tissues in the recycle bin are never late.
+		let due_color = match tissue.resolution {
+			None if tissue.is_overdue(today) => Some(Color::Red),
+			None if tissue.is_due_within(today, due_soon) => Some(Color::Yellow),
+			_ => None,
+		};
+		let mut title = Span::raw(indent.clone());
 		title.content.to_mut().push(match starred {
 			Some(starred) if starred == i => '*',
 			_ => ' ',
 		});
+		if let Some(Some(status)) = statuses.get(i) {
+			title.content.to_mut().push_str(&format!("{status:status_width$} | "));
+		}
 		title.content.to_mut().push_str(&tissue.title);
 		title.content.to_mut().push(' ');
-		if index == i && description_index.is_none() {
+		if index == i && cursor == Cursor::Title {
 			title = title.black().on_white();
+		} else if marked.contains(&tissue.id) {
+			title = title.white().on_blue();
+		} else if let Some(color) = due_color {
+			title = title.fg(color);
 		};
 		let mut title: Line = title.into();
+		let (done, total) = progress(i);
+		if total > 0 {
+			let progress = format!(" [{done}/{total}]");
+			title.spans.push(if done == total { progress.green() } else { progress.dark_gray() });
+		}
+		if let Some(estimate) = tissue.estimate {
+			title.spans.push(format!(" ~{estimate}").dark_gray());
+		}
+		if collapsed.contains(&tissue.id) {
+			let hidden = (0..tissues.len()).filter(|&j| ancestors(tissues, j).contains(&i)).count();
+			title.spans.push(format!(" (+{hidden} hidden)").dark_gray());
+		}
+		if let Some(priority) = tissue.priority {
+			let badge = format!(" [{priority}]");
+			title.spans.push(match priority {
+				Priority::P0 => badge.red().bold(),
+				Priority::P1 => badge.red(),
+				Priority::P2 => badge.yellow(),
+				Priority::P3 => badge.dark_gray(),
+			});
+		}
+		if let Some(resolution) = &tissue.resolution {
+			title.spans.push(format!(" [{resolution}]").green());
+		}
+		let oversized = too_big.map(|too_big| tissue.oversized(too_big, now)).unwrap_or_default();
+		if !oversized.is_empty() {
+			let reasons = oversized.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+			title.spans.push(format!(" [too big: {reasons}; consider publishing]").yellow());
+		}
+		if let Some(due) = tissue.due {
+			title.spans.push(Span::styled(format!(" due {due}"), due_color.unwrap_or(Color::DarkGray)));
+		}
+		if let Some(number) = tissue.issue.as_ref().and_then(|issue| issue.number) {
+			title.spans.push(format!(" #{number}").blue());
+		}
+		if let Some(branch) = &tissue.branch {
+			if checked_out == Some(i) {
+				title.spans.push(format!(" [on {}]", branch.name).cyan().bold());
+			} else {
+				title.spans.push(format!(" [{}]", branch.name).cyan());
+			}
+		}
 		for tag in &tissue.tags {
-			title.spans.push(format!(" ({tag})").magenta());
+			let color = tags.get(tag).and_then(|info| info.color.as_ref()).and_then(|color| color.parse().ok()).unwrap_or(Color::Magenta);
+			title.spans.push(Span::styled(format!(" ({tag})"), color));
+		}
+		if blocked(i) {
+			title = title.dim();
 		}
 		body.lines.push(title);
 		for (di, description) in tissue.description.iter().enumerate() {
-			if let Some(d_index) = description_index {
-				if index == i && d_index == di {
-					body.lines.push(format!(" - {description}").black().on_white().into());
-					continue;
-				}
+			if index == i && cursor == Cursor::Description(di) {
+				body.lines.push(format!("{indent} - {description}").black().on_white().into());
+			} else if description.done {
+				body.lines.push(format!("{indent} - {description}").green().into());
+			} else {
+				body.lines.push(format!("{indent} - {description}").dark_gray().into());
+			}
+		}
+		for (ri, reference) in tissue.references.iter().enumerate() {
+			if index == i && cursor == Cursor::Reference(ri) {
+				body.lines.push(format!("{indent} @ {reference}").black().on_white().into());
+			} else {
+				body.lines.push(format!("{indent} @ {reference}").blue().into());
+			}
+		}
+		for (ai, attachment) in tissue.attachments.iter().enumerate() {
+			if index == i && cursor == Cursor::Attachment(ai) {
+				body.lines.push(format!("{indent} + {attachment}").black().on_white().into());
+			} else {
+				body.lines.push(format!("{indent} + {attachment}").cyan().into());
 			}
-			body.lines.push(format!(" - {description}").dark_gray().into());
 		}
 	}
 }
 
+/// The running timer for the header, such as `*Foo 0:12:05`.
+fn timer(tissue: &Tissue, starred: bool) -> Line<'_> {
+	let seconds = tissue.tracked(None, chrono::Utc::now()).num_seconds();
+	let star = if starred { "*" } else { "" };
+	Line::from(vec![format!(" {star}{} ", tissue.title).into(), format!("{}:{:02}:{:02} ", seconds / 3600, seconds / 60 % 60, seconds % 60).yellow().bold()])
+}
+
+fn preview(body: &mut Text, plan: &publish::Plan) {
+	body.lines.push(Line::from(vec!["Title: ".blue(), plan.title.clone().into()]));
+	body.lines.push(Line::from(vec!["Labels: ".blue(), plan.labels.join(", ").magenta()]));
+	if !plan.create_labels.is_empty() {
+		body.lines.push(Line::from(vec!["Labels to create: ".blue(), plan.create_labels.join(", ").magenta()]));
+	}
+	body.lines.push("Body:".blue().into());
+	for line in plan.body.lines() {
+		body.lines.push(format!("  {line}").into());
+	}
+	body.lines.push(Line::default());
+	body.lines.push("Steps:".blue().into());
+	for step in &plan.steps {
+		body.lines.push(format!("  {step}").dark_gray().into());
+	}
+}
+
 fn instructions(mode: &Mode) -> Title<'_> {
 	match mode {
 		Mode::Normal => Title::from(Line::from(Vec::from([
@@ -443,10 +966,11 @@ fn instructions(mode: &Mode) -> Title<'_> {
 			"uit ".into(),
 		]))),
 		Mode::Help => Title::from(Line::from(Vec::from([" Help! ".blue().bold()]))),
+		Mode::Visual(_) => Title::from(Line::from(Vec::from([" Mark tissues up to here:".blue().bold(), " v".red().bold(), "/".into(), "Space".red().bold(), " mark ".into()]))),
 		Mode::Add(title) => Title::from(Line::from(Vec::from([" Add tissue: ".blue().bold(), title.into(), "_ ".into()]))),
 		Mode::Edit(title) => Title::from(Line::from(Vec::from([" Edit tissue title: ".blue().bold(), title.into(), "_ ".into()]))),
 		Mode::Describe(description) => Title::from(Line::from(Vec::from([" Describe tissue: ".blue().bold(), description.into(), "_ ".into()]))),
-		Mode::Tag(tag) => Title::from(Line::from(Vec::from([" Tag tissue: ".blue().bold(), tag.into(), "_ ".into()]))),
+		Mode::Tag(tag, _) => Title::from(Line::from(Vec::from([" Tag tissue: ".blue().bold(), tag.into(), "_".into(), " (Tab completes) ".dark_gray()]))),
 		Mode::Copy => Title::from(Line::from(Vec::from([
 			" Copy what?:".blue().bold(),
 			" t".red().bold(),
@@ -456,8 +980,18 @@ fn instructions(mode: &Mode) -> Title<'_> {
 			" l".red().bold(),
 			"ist ".into(),
 		]))),
-		Mode::Publish => Title::from(Line::from(Vec::from([" Really Publish?:".blue().bold(), " y".red().bold(), "es".into(), " N".red().bold(), "o ".into()]))),
-		Mode::Commit => Title::from(Line::from(Vec::from([" Really Commit?:".blue().bold(), " y".red().bold(), "es".into(), " N".red().bold(), "o ".into()]))),
+		Mode::Publish(_) => Title::from(Line::from(Vec::from([" Really Publish?:".blue().bold(), " y".red().bold(), "es".into(), " N".red().bold(), "o ".into()]))),
+		Mode::Commit(blockers) if !blockers.is_empty() => Title::from(Line::from(Vec::from([
+			format!(" Still blocked by {}!", blockers.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")).yellow().bold(),
+			" Really Commit?:".blue().bold(),
+			" y".red().bold(),
+			"es".into(),
+			" N".red().bold(),
+			"o".into(),
+			" f".red().bold(),
+			"orce ".into(),
+		]))),
+		Mode::Commit(_) => Title::from(Line::from(Vec::from([" Really Commit?:".blue().bold(), " y".red().bold(), "es".into(), " N".red().bold(), "o".into(), " f".red().bold(), "orce ".into()]))),
 		Mode::Remove => Title::from(Line::from(Vec::from([
 			" Remove what?:".blue().bold(),
 			" T".red().bold(),
@@ -468,8 +1002,11 @@ fn instructions(mode: &Mode) -> Title<'_> {
 			"ag ".into(),
 		]))),
 		Mode::RemoveDescription(_) => Title::from(Line::from(Vec::from([" Remove which description? ".blue().bold()]))),
-		Mode::RemoveTag(tag) => Title::from(Line::from(Vec::from([" Remove tag: ".blue().bold(), tag.into(), "_ ".into()]))),
+		Mode::Check(_) => Title::from(Line::from(Vec::from([" Check off which item? ".blue().bold()]))),
+		Mode::RemoveTag(_) => Title::from(Line::from(Vec::from([" Remove which tag? ".blue().bold()]))),
 		Mode::Restore(_) => Title::from(Line::from(Vec::from([" Select tissue and restore ".blue().bold()]))),
+		Mode::OpenReference(_) => Title::from(Line::from(Vec::from([" Open which reference? ".blue().bold()]))),
+		Mode::OpenAttachment(_) => Title::from(Line::from(Vec::from([" Open which attachment? ".blue().bold()]))),
 	}
 }
 
@@ -482,6 +1019,18 @@ fn help(body: &mut Text) {
 		" t (tag): Assign a tag to the selected tissue".into(),
 		" e (edit): Edit the title of the selected tissue".into(),
 		" r (remove): Delete the selected tissue".into(),
+		" Space (mark): Mark or unmark the selected tissue, and move to the next".into(),
+		" v (visual): Mark every tissue between here and wherever the cursor is moved to".into(),
+		"             Tagging, removing, moving status and publishing act on every marked tissue at once.".into(),
+		"             Esc clears the marks.".into(),
+		" o (open): Open one of the selected tissue's references in $EDITOR".into(),
+		" O (open attachment): Open one of the selected tissue's attachments with xdg-open".into(),
+		" n (notes): Edit the selected tissue's markdown notes in $EDITOR".into(),
+		" Enter (details): Show or hide the selected tissue's notes beside the list".into(),
+		" x (check): Check or uncheck an item in the selected tissue's description".into(),
+		" z (fold): Collapse or expand the subtasks of the selected tissue".into(),
+		" s/S (status): Move the selected tissue to the next or previous status in the workflow".into(),
+		" +/- (priority): Make the selected tissue more or less urgent, from P3 up to P0".into(),
 		// The below should be moved to an "advanced" section should they reach ~3 or 4 buttons
 		" R (restore): Restore a deleted tissue".into(),
 		" * (star): Marks the tissue with a *.".into(),
@@ -494,7 +1043,134 @@ fn help(body: &mut Text) {
 		" C (commit): Add all files to the git index and commit.".into(),
 		"             Uses the selected tissue's title as the message".into(),
 		"             Equivalent to `git add --all && git commit -m {title}`".into(),
-		" P (publish): Publish the selected issue to GitHub. Requires the `gh` command.".into(),
+		"             With require_checked set, f commits even if some items are unchecked".into(),
+		"             Warns first if the tissue is still blocked; blocked tissues are dimmed".into(),
+		" P (publish): Publish the selected issue to the configured issue tracker, keeping it linked for `tissue sync`.".into(),
+		"              Uses GitHub through the `gh` command unless configured otherwise.".into(),
+		"              Tissues past the too_big limits in the config are flagged as candidates.".into(),
 	];
 	*body = help.into_iter().collect();
 }
+
+/// Render markdown notes, styling headings, emphasis, code, lists, quotes and links.
+pub fn markdown(notes: &str) -> Text<'static> {
+	use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+	let mut text = Text::default();
+	let mut line: Vec<Span<'static>> = Vec::new();
+	let mut styles = vec![Style::default()];
+	// The next number of each ordered list being rendered, or none for bullet lists.
+	let mut lists: Vec<Option<u64>> = Vec::new();
+	let mut links = Vec::new();
+	let mut quotes = 0;
+	let mut code_block = false;
+
+	let flush = |text: &mut Text<'static>, line: &mut Vec<Span<'static>>, quotes: usize| {
+		let mut spans = vec![Span::raw("│ ".repeat(quotes)).dark_gray()];
+		spans.append(line);
+		text.lines.push(Line::from(spans));
+	};
+	let blank = |text: &mut Text<'static>| {
+		if text.lines.last().is_some_and(|line| line.width() > 0) {
+			text.lines.push(Line::default());
+		}
+	};
+
+	for event in Parser::new_ext(notes, Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH) {
+		let style = *styles.last().expect("base style is never popped");
+		match event {
+			Event::Start(Tag::Heading { level, .. }) => {
+				let color = match level {
+					HeadingLevel::H1 => Color::Magenta,
+					HeadingLevel::H2 => Color::Blue,
+					_ => Color::Cyan,
+				};
+				styles.push(style.fg(color).add_modifier(Modifier::BOLD));
+				line.push(Span::styled("#".repeat(level as usize) + " ", style.fg(color)));
+			}
+			Event::Start(Tag::Emphasis) => styles.push(style.add_modifier(Modifier::ITALIC)),
+			Event::Start(Tag::Strong) => styles.push(style.add_modifier(Modifier::BOLD)),
+			Event::Start(Tag::Strikethrough) => styles.push(style.add_modifier(Modifier::CROSSED_OUT)),
+			Event::Start(Tag::Link { dest_url, .. }) => {
+				links.push(dest_url);
+				styles.push(style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED));
+			}
+			Event::End(TagEnd::Heading(_)) => {
+				styles.pop();
+				flush(&mut text, &mut line, quotes);
+				blank(&mut text);
+			}
+			Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough) => {
+				styles.pop();
+			}
+			Event::End(TagEnd::Link) => {
+				styles.pop();
+				if let Some(url) = links.pop() {
+					line.push(format!(" <{url}>").dark_gray());
+				}
+			}
+			Event::End(TagEnd::Paragraph) => {
+				flush(&mut text, &mut line, quotes);
+				if lists.is_empty() {
+					blank(&mut text);
+				}
+			}
+			Event::Start(Tag::CodeBlock(_)) => code_block = true,
+			Event::End(TagEnd::CodeBlock) => {
+				code_block = false;
+				blank(&mut text);
+			}
+			Event::Start(Tag::BlockQuote(_)) => quotes += 1,
+			Event::End(TagEnd::BlockQuote(_)) => quotes -= 1,
+			Event::Start(Tag::List(start)) => {
+				if !line.is_empty() {
+					flush(&mut text, &mut line, quotes);
+				}
+				lists.push(start);
+			}
+			Event::End(TagEnd::List(_)) => {
+				lists.pop();
+				if lists.is_empty() {
+					blank(&mut text);
+				}
+			}
+			Event::Start(Tag::Item) => {
+				let indent = "  ".repeat(lists.len().saturating_sub(1));
+				let bullet = match lists.last_mut() {
+					Some(Some(number)) => {
+						*number += 1;
+						format!("{indent}{}. ", *number - 1)
+					}
+					_ => format!("{indent}• "),
+				};
+				line.push(bullet.yellow());
+			}
+			Event::End(TagEnd::Item) if !line.is_empty() => flush(&mut text, &mut line, quotes),
+			Event::TaskListMarker(done) => line.push(if done { "[x] ".green() } else { "[ ] ".dark_gray() }),
+			Event::Text(content) if code_block => {
+				for code in content.lines() {
+					line.push("  ".into());
+					line.push(code.to_owned().yellow());
+					flush(&mut text, &mut line, quotes);
+				}
+			}
+			Event::Text(content) => line.push(Span::styled(content.into_string(), style)),
+			Event::Code(code) => line.push(code.into_string().yellow()),
+			Event::SoftBreak => line.push(" ".into()),
+			Event::HardBreak => flush(&mut text, &mut line, quotes),
+			Event::Rule => {
+				text.lines.push("────────".dark_gray().into());
+				blank(&mut text);
+			}
+			_ => {}
+		}
+	}
+	if !line.is_empty() {
+		flush(&mut text, &mut line, quotes);
+	}
+	// Drop the blank line left after the last block.
+	if text.lines.last().is_some_and(|line| line.width() == 0) {
+		text.lines.pop();
+	}
+	text
+}
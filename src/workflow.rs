@@ -0,0 +1,69 @@
+//! Statuses a tissue moves through between being added and being committed.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("no status named {0:?}; expected one of {1}")]
+	Unknown(String, String),
+	#[error("can't move from {from:?} to {to:?}")]
+	NotAllowed { from: String, to: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Workflow settings, from the `[config.workflow]` table.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Workflow {
+	/// Every status in order. New tissues start in the first, and committed ones finish in the last.
+	pub statuses: Vec<String>,
+	/// Which statuses a status may move to. Statuses left out may move to any other.
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub transitions: BTreeMap<String, Vec<String>>,
+	/// Keep committed tissues in the box in the last status, rather than moving them to the recycle bin.
+	pub keep_history: bool,
+}
+
+impl Default for Workflow {
+	fn default() -> Self {
+		Self {
+			statuses: ["todo", "in progress", "waiting", "review", "done"].map(String::from).to_vec(),
+			transitions: BTreeMap::new(),
+			keep_history: false,
+		}
+	}
+}
+
+impl Workflow {
+	/// The status of tissues that haven't been given one.
+	pub fn initial(&self) -> Option<&str> {
+		self.statuses.first().map(String::as_str)
+	}
+
+	/// The status committed tissues are kept in.
+	pub fn done(&self) -> Option<&str> {
+		self.statuses.last().map(String::as_str)
+	}
+
+	/// Check that a tissue may move from `from` to `to`.
+	pub fn check(&self, from: Option<&str>, to: &str) -> Result<()> {
+		if !self.statuses.iter().any(|status| status == to) {
+			return Err(Error::Unknown(to.into(), self.statuses.join(", ")));
+		}
+		match from.and_then(|from| Some((from, self.transitions.get(from)?))) {
+			Some((from, allowed)) if from != to && !allowed.iter().any(|status| status == to) => Err(Error::NotAllowed { from: from.into(), to: to.into() }),
+			_ => Ok(()),
+		}
+	}
+
+	/// The status after `from` that it may move to, wrapping around, or before it if `backwards`.
+	pub fn cycle(&self, from: Option<&str>, backwards: bool) -> Option<&str> {
+		let position = self.statuses.iter().position(|status| Some(status.as_str()) == from).unwrap_or(0);
+		let count = self.statuses.len();
+		(1..count)
+			.map(|step| if backwards { (position + count - step) % count } else { (position + step) % count })
+			.map(|index| self.statuses[index].as_str())
+			.find(|to| self.check(from, to).is_ok())
+	}
+}